@@ -10,11 +10,7 @@
 //! use tracekit_cachekit::CachekitAdapter;
 //! use cachekit::policy::lru::LruCore;
 //!
-//! let spec = WorkloadSpec {
-//!     universe: 10_000,
-//!     workload: Workload::Zipfian { exponent: 1.0 },
-//!     seed: 42,
-//! };
+//! let spec = WorkloadSpec::new(10_000, Workload::Zipfian { exponent: 1.0 }, 42);
 //! let mut source = BoundedGenerator::new(spec.generator(), 100_000);
 //!
 //! let cache = LruCore::<u64, ()>::new(1000);
@@ -79,6 +75,10 @@ impl<C> CachekitAdapter<C> {
 //         self.cache.insert(K::from(key), V::default());
 //     }
 //
+//     fn contains(&self, key: u64) -> bool {
+//         self.cache.contains(&K::from(key))
+//     }
+//
 //     fn delete(&mut self, key: u64) {
 //         self.cache.remove(&K::from(key));
 //     }
@@ -101,6 +101,10 @@ where
         self.cache.insert_key(key);
     }
 
+    fn contains(&self, key: u64) -> bool {
+        self.cache.contains_key(key)
+    }
+
     fn delete(&mut self, key: u64) {
         self.cache.delete_key(key);
     }
@@ -112,6 +116,9 @@ where
 pub trait SimpleCacheLike {
     fn get_key(&mut self, key: u64) -> bool;
     fn insert_key(&mut self, key: u64);
+    fn contains_key(&self, _key: u64) -> bool {
+        false
+    }
     fn delete_key(&mut self, key: u64);
 }
 
@@ -0,0 +1,62 @@
+//! Helpers for writing JSON benchmark artifacts that `render` can consume.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use tracekit::json_results::{BenchmarkArtifact, BenchmarkConfig, RunMetadata};
+
+/// Capture the current environment for an artifact's metadata.
+pub fn run_metadata(config: BenchmarkConfig) -> RunMetadata {
+    RunMetadata {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        git_commit: command_output("git", &["rev-parse", "HEAD"]),
+        git_branch: command_output("git", &["rev-parse", "--abbrev-ref", "HEAD"]),
+        git_dirty: command_output("git", &["status", "--porcelain"]).is_some(),
+        rustc_version: command_output("rustc", &["--version"])
+            .unwrap_or_else(|| "unknown".to_string()),
+        host_triple: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        cpu_model: cpu_model(),
+        config,
+    }
+}
+
+/// Serialize `artifact` as pretty-printed JSON.
+pub fn write_artifact<W: Write>(
+    mut writer: W,
+    artifact: &BenchmarkArtifact,
+) -> Result<(), Box<dyn std::error::Error>> {
+    serde_json::to_writer_pretty(&mut writer, artifact)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Workload id and display name for results measured on the trace at `path`.
+pub fn trace_workload(path: &Path) -> (String, String) {
+    let id = path
+        .file_stem()
+        .map_or_else(|| "trace".to_string(), |s| s.to_string_lossy().into_owned());
+    let name = path
+        .file_name()
+        .map_or_else(|| "trace".to_string(), |s| s.to_string_lossy().into_owned());
+    (id, name)
+}
+
+/// Trimmed stdout of a successful command, or `None` if it failed or was empty.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split(':').nth(1))
+        .map(|model| model.trim().to_string())
+}
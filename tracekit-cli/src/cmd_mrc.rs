@@ -0,0 +1,296 @@
+//! `mrc` command - Sweep cache capacity and write miss-ratio curves.
+//!
+//! The trace is loaded into memory once, then every (policy, capacity) pair is
+//! replayed on a pool of worker threads. LRU is the exception: its whole curve
+//! is read off one pass of exact stack distances (`tracekit::mrc`). Output is
+//! CSV or a JSON artifact with `mrc` results that `render` can chart.
+
+use clap::Args;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracekit::json_results::{
+    BenchmarkArtifact, BenchmarkConfig, Metrics, MrcPoint, MrcStats, ResultRow,
+};
+use tracekit::metrics::HitStats;
+use tracekit::mrc::{MattsonStack, MrcMethod, StackDistanceHistogram, analyze};
+use tracekit::{Event, OfflineTrace, PolicyRegistry, simulate};
+
+use crate::artifact;
+use crate::cmd_simulate::{InputFormat, open_trace};
+use crate::policy::PolicyChoice;
+
+#[derive(Args)]
+pub struct MrcArgs {
+    /// Input trace file
+    #[arg(short, long)]
+    trace: PathBuf,
+
+    /// Input format
+    #[arg(short, long, value_enum, default_value = "key-only")]
+    format: InputFormat,
+
+    /// Policy spec, e.g. `lru`, `2q:kin=0.25` or `opt` (repeatable)
+    #[arg(short, long = "policy", default_value = "lru")]
+    policies: Vec<String>,
+
+    /// Smallest capacity (default: 1% of unique keys)
+    #[arg(long)]
+    min: Option<f64>,
+
+    /// Largest capacity (default: number of unique keys)
+    #[arg(long)]
+    max: Option<f64>,
+
+    /// Number of capacities in the sweep
+    #[arg(long, default_value = "20")]
+    points: usize,
+
+    /// Spacing between capacities
+    #[arg(long, value_enum, default_value = "log")]
+    spacing: Spacing,
+
+    /// Interpret --min/--max as fractions of the trace's unique keys
+    #[arg(long)]
+    relative: bool,
+
+    /// Worker threads (default: available parallelism)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "csv")]
+    output_format: OutputFormat,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Spacing {
+    /// Evenly spaced capacities
+    Linear,
+    /// Geometrically spaced capacities
+    Log,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// One row per (policy, capacity)
+    Csv,
+    /// Benchmark artifact for `render`
+    Json,
+}
+
+pub fn run(args: MrcArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = PolicyRegistry::new();
+    let policies = PolicyChoice::parse_all(&registry, &args.policies)?;
+
+    let mut source = open_trace(&args.trace, args.format)?;
+    let events: Vec<Event> = std::iter::from_fn(|| source.next_event()).collect();
+    let unique_keys = count_unique(&events);
+
+    let scale = if args.relative {
+        unique_keys as f64
+    } else {
+        1.0
+    };
+    let min = args.min.map_or(unique_keys as f64 * 0.01, |m| m * scale);
+    let max = args.max.map_or(unique_keys as f64, |m| m * scale);
+    if !(min > 0.0 && max >= min) {
+        return Err(format!("invalid capacity range: {min} to {max}").into());
+    }
+    let capacities = capacity_range(min, max, args.points, args.spacing);
+
+    let optimal = policies
+        .contains(&PolicyChoice::Optimal)
+        .then(|| OfflineTrace::from_source(&mut events.iter().copied()));
+
+    let stack_distances = policies
+        .iter()
+        .any(is_lru)
+        .then(|| analyze(MattsonStack::new(), &mut events.iter().copied()));
+
+    let jobs: Vec<(&PolicyChoice, usize)> = policies
+        .iter()
+        .flat_map(|policy| capacities.iter().map(move |&capacity| (policy, capacity)))
+        .collect();
+    let threads = args
+        .threads
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .clamp(1, jobs.len().max(1));
+
+    eprintln!(
+        "Sweeping {} capacities x {} policies over {} events ({} unique keys) on {} thread{}",
+        capacities.len(),
+        policies.len(),
+        events.len(),
+        unique_keys,
+        threads,
+        if threads == 1 { "" } else { "s" }
+    );
+
+    let next_job = AtomicUsize::new(0);
+    let results = Mutex::new(vec![HitStats::default(); jobs.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                loop {
+                    let idx = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(policy, capacity)) = jobs.get(idx) else {
+                        break;
+                    };
+                    let stats = if is_lru(policy) {
+                        let histogram = stack_distances.as_ref().expect("stack distances for lru");
+                        stack_distance_stats(histogram, capacity)
+                    } else {
+                        match policy.build(&registry, capacity) {
+                            Some(mut cache) => simulate(&mut cache, &mut events.iter().copied()),
+                            None => optimal
+                                .as_ref()
+                                .expect("offline trace built for opt")
+                                .belady(capacity),
+                        }
+                    };
+                    results.lock().unwrap()[idx] = stats;
+                }
+            });
+        }
+    });
+    let results = results.into_inner().unwrap();
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+
+    match args.output_format {
+        OutputFormat::Csv => {
+            let mut writer = writer;
+            writeln!(
+                writer,
+                "policy,capacity,capacity_fraction,hits,misses,miss_ratio"
+            )?;
+            for (&(policy, capacity), stats) in jobs.iter().zip(&results) {
+                writeln!(
+                    writer,
+                    "{},{},{:.6},{},{},{:.6}",
+                    csv_field(&policy.id()),
+                    capacity,
+                    capacity as f64 / unique_keys.max(1) as f64,
+                    stats.hits,
+                    stats.misses,
+                    stats.miss_rate()
+                )?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Json => {
+            let config = BenchmarkConfig {
+                capacity: capacities.last().copied().unwrap_or(0),
+                universe: unique_keys as u64,
+                operations: events.len(),
+                seed: 0,
+                warmup: None,
+            };
+            let mut artifact = BenchmarkArtifact::new(artifact::run_metadata(config));
+            let (workload_id, workload_name) = artifact::trace_workload(&args.trace);
+
+            for (chunk, policy) in results.chunks(capacities.len()).zip(&policies) {
+                let points = capacities
+                    .iter()
+                    .zip(chunk)
+                    .map(|(&capacity, stats)| MrcPoint {
+                        capacity,
+                        miss_ratio: stats.miss_rate(),
+                    })
+                    .collect();
+                artifact.add_result(ResultRow {
+                    policy_id: policy.id(),
+                    policy_name: policy.display_name().to_string(),
+                    workload_id: workload_id.clone(),
+                    workload_name: workload_name.clone(),
+                    case_id: "mrc".to_string(),
+                    capacity: capacities.last().copied(),
+                    seed: None,
+                    metrics: Metrics {
+                        mrc: Some(MrcStats {
+                            method: if policy.is_optimal() {
+                                "belady".to_string()
+                            } else if is_lru(policy) {
+                                MrcMethod::Exact.id().to_string()
+                            } else {
+                                "simulation".to_string()
+                            },
+                            points,
+                        }),
+                        ..Metrics::default()
+                    },
+                });
+            }
+            artifact::write_artifact(writer, &artifact)?;
+        }
+    }
+
+    if let Some(path) = &args.output {
+        eprintln!("Wrote miss-ratio curves to {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a delimiter (e.g. `2q:kin=0.2,kout=0.4`).
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Plain LRU, whose curve comes from stack distances instead of replays.
+fn is_lru(policy: &PolicyChoice) -> bool {
+    matches!(policy, PolicyChoice::Online { spec, .. } if spec.id == "lru")
+}
+
+/// LRU hits and misses at `capacity`, read off exact stack distances.
+fn stack_distance_stats(histogram: &StackDistanceHistogram, capacity: usize) -> HitStats {
+    let hits = histogram.hits(capacity) as u64;
+    HitStats {
+        hits,
+        misses: histogram.requests() as u64 - hits,
+        ..HitStats::default()
+    }
+}
+
+fn count_unique(events: &[Event]) -> usize {
+    let mut keys: Vec<u64> = events.iter().map(|e| e.key).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys.len()
+}
+
+/// `points` capacities from `min` to `max` inclusive, rounded and deduplicated.
+fn capacity_range(min: f64, max: f64, points: usize, spacing: Spacing) -> Vec<usize> {
+    let points = points.max(1);
+    let mut capacities: Vec<usize> = (0..points)
+        .map(|i| {
+            let t = if points == 1 {
+                1.0
+            } else {
+                i as f64 / (points - 1) as f64
+            };
+            let value = match spacing {
+                Spacing::Linear => min + (max - min) * t,
+                Spacing::Log => (min.ln() + (max.ln() - min.ln()) * t).exp(),
+            };
+            (value.round() as usize).max(1)
+        })
+        .collect();
+    capacities.dedup();
+    capacities
+}
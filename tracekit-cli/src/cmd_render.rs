@@ -132,6 +132,13 @@ fn generate_markdown(artifact: &BenchmarkArtifact) -> String {
         md.push('\n');
     }
 
+    // Miss-Ratio Curves
+    if let Some(mrc_results) = by_case.get("mrc") {
+        md.push_str("## Miss-Ratio Curves\n\n");
+        md.push_str(&generate_mrc_table(mrc_results));
+        md.push('\n');
+    }
+
     // Policy Selection Guide
     md.push_str("## Policy Selection Guide\n\n");
     md.push_str(&generate_policy_guide());
@@ -375,6 +382,65 @@ fn generate_adaptation_table(results: &[&ResultRow]) -> String {
     md
 }
 
+fn generate_mrc_table(results: &[&ResultRow]) -> String {
+    let mut md = String::new();
+
+    // One column per curve, one row per capacity
+    let mut curves: Vec<(String, HashMap<usize, f64>)> = Vec::new();
+    let mut capacities = Vec::new();
+
+    for result in results {
+        if let Some(ref mrc) = result.metrics.mrc {
+            let label = format!(
+                "{} / {} ({})",
+                result.policy_name, result.workload_name, mrc.method
+            );
+            let points: HashMap<usize, f64> = mrc
+                .points
+                .iter()
+                .map(|p| (p.capacity, p.miss_ratio))
+                .collect();
+            for &capacity in points.keys() {
+                if !capacities.contains(&capacity) {
+                    capacities.push(capacity);
+                }
+            }
+            curves.push((label, points));
+        }
+    }
+
+    curves.sort_by(|a, b| a.0.cmp(&b.0));
+    capacities.sort_unstable();
+
+    md.push_str("| Capacity |");
+    for (label, _) in &curves {
+        md.push_str(&format!(" {} |", label));
+    }
+    md.push('\n');
+
+    md.push_str("|---------:|");
+    for _ in &curves {
+        md.push_str("-------:|");
+    }
+    md.push('\n');
+
+    for capacity in &capacities {
+        md.push_str(&format!("| {} |", capacity));
+        for (_, points) in &curves {
+            if let Some(&miss_ratio) = points.get(capacity) {
+                md.push_str(&format!(" {:.2}% |", miss_ratio * 100.0));
+            } else {
+                md.push_str(" - |");
+            }
+        }
+        md.push('\n');
+    }
+
+    md.push_str("\n*Values are miss ratios (lower is better)*\n");
+
+    md
+}
+
 fn generate_policy_guide() -> String {
     let mut md = String::new();
 
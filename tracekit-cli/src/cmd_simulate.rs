@@ -1,11 +1,29 @@
 //! `simulate` command - Run cache simulation on a trace.
 //!
-//! Note: This command requires a cache implementation. Since tracekit-cli
-//! doesn't bundle a default cache, this is currently a placeholder that
-//! demonstrates the simulation API.
+//! Replays the trace once through every selected reference policy from
+//! the policy registry (LRU by default), spread over worker threads, and
+//! prints a side-by-side comparison.
+//! The offline optimal policy (`opt`) can be included as an upper bound.
+//! `--timeseries` additionally writes per-window hit rates of the online
+//! policies as CSV.
 
 use clap::Args;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracekit::json_results::{BenchmarkArtifact, BenchmarkConfig, Metrics, ResultRow};
+use tracekit::metrics::HitStats;
+use tracekit::registry::BoxedCacheModel;
+use tracekit::timeseries::{WindowBy, WindowStats, WindowedStats};
+use tracekit::{
+    Event, EventSource, OfflineTrace, Op, PolicyRegistry, ReplayMode, Warmup, WarmupFilter,
+    WritePolicy, simulate_many_observed,
+};
+
+use crate::artifact;
+use crate::cmd_mrc::csv_field;
+use crate::policy::PolicyChoice;
 
 #[derive(Args)]
 pub struct SimulateArgs {
@@ -20,6 +38,63 @@ pub struct SimulateArgs {
     /// Input format
     #[arg(short, long, value_enum, default_value = "key-only")]
     format: InputFormat,
+
+    /// Policy spec, e.g. `lru`, `2q:kin=0.25` or `opt` (repeatable; all run in a single pass)
+    #[arg(short, long = "policy", default_value = "lru")]
+    policies: Vec<String>,
+
+    /// Also report the offline optimal (Belady) hit rate (same as `--policy opt`)
+    #[arg(long)]
+    opt: bool,
+
+    /// Write a JSON benchmark artifact (case `hit_rate`) for `render`
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Write per-window hit rates of the online policies as CSV
+    #[arg(long)]
+    timeseries: Option<PathBuf>,
+
+    /// Events per time-series window
+    #[arg(long, default_value = "10000")]
+    window: u64,
+
+    /// Split the time series by timestamp instead, in trace time units per window
+    #[arg(long)]
+    window_time: Option<u64>,
+
+    /// Worker threads for the online policies (default: available parallelism)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Exclude a warmup from the statistics: an event count (`50000`), a
+    /// fraction of the trace (`10%`), or `full` (until the first eviction)
+    #[arg(long, value_parser = parse_warmup)]
+    warmup: Option<Warmup>,
+
+    /// How explicit writes (inserts) reach the online policies; `opt` always allocates
+    #[arg(long, value_enum, default_value = "allocate-on-miss")]
+    write_policy: WriteMode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WriteMode {
+    /// Update resident keys, insert missing ones
+    AllocateOnMiss,
+    /// Update resident keys only (write-through, no allocation)
+    Through,
+    /// Never cache writes; invalidate resident copies (write-around)
+    Around,
+}
+
+impl From<WriteMode> for WritePolicy {
+    fn from(mode: WriteMode) -> Self {
+        match mode {
+            WriteMode::AllocateOnMiss => WritePolicy::AllocateOnMiss,
+            WriteMode::Through => WritePolicy::Through,
+            WriteMode::Around => WritePolicy::Around,
+        }
+    }
 }
 
 #[derive(Clone, Copy, clap::ValueEnum)]
@@ -39,125 +114,339 @@ pub enum InputFormat {
 }
 
 pub fn run(args: SimulateArgs) -> Result<(), Box<dyn std::error::Error>> {
-    use std::collections::HashMap;
-    use std::fs::File;
-    use std::io::BufReader;
-    use tracekit::{CacheModel, simulate};
-    use tracekit_formats::KeyOnlyReader;
-
-    // Simple LRU implementation for demonstration
-    struct SimpleLru {
-        capacity: usize,
-        map: HashMap<u64, usize>,
-        order: Vec<u64>,
+    let registry = PolicyRegistry::new();
+    let mut policies = PolicyChoice::parse_all(&registry, &args.policies)?;
+    if args.opt && !policies.contains(&PolicyChoice::Optimal) {
+        policies.push(PolicyChoice::Optimal);
     }
 
-    impl SimpleLru {
-        fn new(capacity: usize) -> Self {
-            Self {
-                capacity,
-                map: HashMap::with_capacity(capacity),
-                order: Vec::with_capacity(capacity),
-            }
+    let window = args.timeseries.as_ref().map(|_| match args.window_time {
+        Some(width) => WindowBy::Time(width),
+        None => WindowBy::Events(args.window),
+    });
+
+    let threads = args
+        .threads
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1);
+
+    let warmup = args.warmup.unwrap_or(Warmup::Events(0));
+    // A fractional warmup needs the trace length up front.
+    let trace_len = match warmup {
+        Warmup::Fraction(_) => {
+            let mut source = open_trace(&args.trace, args.format)?;
+            Some(std::iter::from_fn(|| source.next_event()).count() as u64)
         }
+        _ => None,
+    };
+
+    let mut source = open_trace(&args.trace, args.format)?;
+    let results = replay(
+        source.as_mut(),
+        &registry,
+        &policies,
+        args.capacity,
+        ReplayOptions {
+            window,
+            threads,
+            warmup,
+            trace_len,
+            mode: ReplayMode {
+                writes: args.write_policy.into(),
+                // Only the JSON artifact reports the miss breakdown.
+                classify_misses: args.json.is_some(),
+                // Every registered policy reports residency.
+                count_updates: true,
+                ..ReplayMode::default()
+            },
+        },
+    );
+
+    println!("Simulation Results:");
+    println!("  Trace: {}", args.trace.display());
+    println!("  Cache capacity: {}", args.capacity);
+    println!("  Total requests: {}", results.total_requests);
+    if let Some(warmup) = args.warmup {
+        println!("  Warmup: {}", describe_warmup(warmup));
+    }
+    if args.write_policy != WriteMode::AllocateOnMiss {
+        println!(
+            "  Write policy: {}",
+            describe_write_policy(args.write_policy)
+        );
+    }
+    println!();
+    let width = policies
+        .iter()
+        .map(|p| p.display_name().len())
+        .max()
+        .unwrap_or(0)
+        .max("Policy".len());
+    println!(
+        "  {:<width$} {:>9} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "Policy", "Hit rate", "Hits", "Misses", "Inserts", "Updates", "Bypassed", "Evictions"
+    );
+    for (policy, stats) in policies.iter().zip(&results.stats) {
+        println!(
+            "  {:<width$} {:>8.2}% {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            policy.display_name(),
+            stats.hit_rate() * 100.0,
+            stats.hits,
+            stats.misses,
+            stats.inserts,
+            stats.updates,
+            stats.bypasses,
+            stats.evictions
+        );
     }
 
-    impl CacheModel for SimpleLru {
-        fn get(&mut self, key: u64) -> bool {
-            if self.map.contains_key(&key) {
-                // Move to end (most recently used)
-                if let Some(pos) = self.order.iter().position(|&k| k == key) {
-                    self.order.remove(pos);
-                    self.order.push(key);
-                }
-                true
-            } else {
-                false
-            }
+    if let Some(path) = &args.json {
+        let config = BenchmarkConfig {
+            capacity: args.capacity,
+            universe: results.unique_keys as u64,
+            operations: results.total_events,
+            seed: 0,
+            warmup: args.warmup.map(Into::into),
+        };
+        let mut artifact = BenchmarkArtifact::new(artifact::run_metadata(config));
+        let (workload_id, workload_name) = artifact::trace_workload(&args.trace);
+        for (policy, stats) in policies.iter().zip(&results.stats) {
+            artifact.add_result(ResultRow {
+                policy_id: policy.id(),
+                policy_name: policy.display_name().to_string(),
+                workload_id: workload_id.clone(),
+                workload_name: workload_name.clone(),
+                case_id: "hit_rate".to_string(),
+                capacity: Some(args.capacity),
+                seed: None,
+                metrics: Metrics {
+                    hit_stats: Some(stats.into()),
+                    eviction: Some((&stats.eviction_stats()).into()),
+                    ..Metrics::default()
+                },
+            });
         }
+        artifact::write_artifact(BufWriter::new(File::create(path)?), &artifact)?;
+        eprintln!("Wrote results to {}", path.display());
+    }
 
-        fn insert(&mut self, key: u64) {
-            if self.map.contains_key(&key) {
-                // Already present, just update order
-                if let Some(pos) = self.order.iter().position(|&k| k == key) {
-                    self.order.remove(pos);
-                    self.order.push(key);
-                }
-                return;
-            }
+    if let Some(path) = &args.timeseries {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_timeseries(&mut writer, &policies, &results.series)?;
+        writer.flush()?;
+        eprintln!("Wrote time series to {}", path.display());
+    }
 
-            // Evict if at capacity
-            if self.order.len() >= self.capacity {
-                if let Some(evicted) = self.order.first().copied() {
-                    self.order.remove(0);
-                    self.map.remove(&evicted);
-                }
-            }
+    Ok(())
+}
 
-            // Insert new key
-            self.map.insert(key, 0);
-            self.order.push(key);
+/// Write one CSV row per policy and window. Timestamps are empty when the
+/// trace has none.
+fn write_timeseries<W: Write>(
+    writer: &mut W,
+    policies: &[PolicyChoice],
+    series: &[Vec<WindowStats>],
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "policy,window,first_event,events,start_ts,end_ts,hits,misses,hit_rate,evictions"
+    )?;
+    let ts = |ts: Option<u64>| ts.map(|ts| ts.to_string()).unwrap_or_default();
+    for (policy, windows) in policies.iter().zip(series) {
+        let id = csv_field(&policy.id());
+        for window in windows {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{:.6},{}",
+                id,
+                window.index,
+                window.first_event,
+                window.events,
+                ts(window.start_ts),
+                ts(window.end_ts),
+                window.stats.hits,
+                window.stats.misses,
+                window.stats.hit_rate(),
+                window.stats.evictions
+            )?;
         }
+    }
+    Ok(())
+}
 
-        fn delete(&mut self, key: u64) {
-            if self.map.remove(&key).is_some() {
-                if let Some(pos) = self.order.iter().position(|&k| k == key) {
-                    self.order.remove(pos);
-                }
-            }
+/// Parse `--warmup`: an event count, a percentage, or `full`.
+fn parse_warmup(value: &str) -> Result<Warmup, String> {
+    if value.eq_ignore_ascii_case("full") {
+        return Ok(Warmup::UntilFull);
+    }
+    if let Some(percent) = value.strip_suffix('%') {
+        let percent: f64 = percent
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid warmup percentage '{value}'"))?;
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(format!("warmup percentage '{value}' is not within 0-100%"));
         }
+        return Ok(Warmup::Fraction(percent / 100.0));
     }
+    value
+        .parse()
+        .map(Warmup::Events)
+        .map_err(|_| format!("invalid warmup '{value}' (expected N, N% or full)"))
+}
 
-    let file = File::open(&args.trace)?;
-    let reader = BufReader::new(file);
+fn describe_warmup(warmup: Warmup) -> String {
+    match warmup {
+        Warmup::Events(n) => format!("first {n} events"),
+        Warmup::Fraction(fraction) => format!("first {:.1}% of the trace", fraction * 100.0),
+        Warmup::UntilFull => "until the first eviction".to_string(),
+    }
+}
 
-    let stats = match args.format {
-        InputFormat::KeyOnly => {
-            let mut source = KeyOnlyReader::new(reader);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
-        }
-        InputFormat::Jsonl => {
-            use tracekit_formats::JsonlReader;
-            let mut source = JsonlReader::new(reader);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
-        }
-        InputFormat::Arc => {
-            use tracekit_formats::ArcReader;
-            let mut source = ArcReader::new(reader);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
+fn describe_write_policy(mode: WriteMode) -> &'static str {
+    match mode {
+        WriteMode::AllocateOnMiss => "write-allocate",
+        WriteMode::Through => "write-through",
+        WriteMode::Around => "write-around",
+    }
+}
+
+/// Open `path` as an event stream in the given format.
+pub fn open_trace(path: &Path, format: InputFormat) -> std::io::Result<Box<dyn EventSource>> {
+    use tracekit_formats::{
+        ArcReader, CachelibReader, CsvConfig, CsvReader, JsonlReader, KeyOnlyReader, LirsReader,
+    };
+
+    let reader = BufReader::new(File::open(path)?);
+    let source: Box<dyn EventSource> = match format {
+        InputFormat::KeyOnly => Box::new(KeyOnlyReader::new(reader)),
+        InputFormat::Jsonl => Box::new(JsonlReader::new(reader)),
+        InputFormat::Arc => Box::new(ArcReader::new(reader)),
+        InputFormat::Lirs => Box::new(LirsReader::new(reader)),
+        // Default to key-only, can be extended
+        InputFormat::Csv => Box::new(CsvReader::new(reader, CsvConfig::key_only())),
+        InputFormat::Cachelib => Box::new(CachelibReader::with_defaults(reader)),
+    };
+    Ok(source)
+}
+
+/// Outcome of replaying one trace through several policies.
+struct ReplayResults {
+    /// Stats per policy, in the order requested.
+    stats: Vec<HitStats>,
+    /// Per-window stats per policy; empty for `opt` or without a window.
+    series: Vec<Vec<WindowStats>>,
+    total_events: usize,
+    total_requests: u64,
+    unique_keys: usize,
+}
+
+/// How `replay` runs the online policies.
+struct ReplayOptions {
+    /// Time-series window, if `--timeseries` was requested.
+    window: Option<WindowBy>,
+    threads: usize,
+    warmup: Warmup,
+    /// Trace length, needed for a fractional warmup.
+    trace_len: Option<u64>,
+    mode: ReplayMode,
+}
+
+/// Read `source` once and replay it through every policy.
+///
+/// Online models run through `simulate_many_observed` on up to
+/// `options.threads` workers, so every policy sees exactly the same event
+/// sequence. Each model's observer counts its statistics behind a
+/// `WarmupFilter` and, with a window, also feeds a `WindowedStats` recorder.
+/// The offline optimal needs the whole trace up front; when it is selected,
+/// events are also buffered.
+fn replay(
+    source: &mut dyn EventSource,
+    registry: &PolicyRegistry,
+    policies: &[PolicyChoice],
+    capacity: usize,
+    options: ReplayOptions,
+) -> ReplayResults {
+    let (online_idx, mut caches): (Vec<usize>, Vec<BoxedCacheModel>) = policies
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, policy)| policy.build(registry, capacity).map(|cache| (idx, cache)))
+        .unzip();
+    let mut windows: Vec<Option<(WindowedStats, Vec<WindowStats>)>> = caches
+        .iter()
+        .map(|_| {
+            options
+                .window
+                .map(|by| (WindowedStats::new(by), Vec::new()))
+        })
+        .collect();
+    let mut buffered: Option<Vec<Event>> = policies.contains(&PolicyChoice::Optimal).then(Vec::new);
+    let mut keys = HashSet::new();
+    let mut total_events = 0;
+    let mut total_requests = 0;
+
+    // Trace-level counts are gathered as the reader pulls each event.
+    let mut events = std::iter::from_fn(|| {
+        let event = source.next_event()?;
+        total_events += 1;
+        keys.insert(event.key);
+        if event.op == Op::Get {
+            total_requests += 1;
         }
-        InputFormat::Lirs => {
-            use tracekit_formats::LirsReader;
-            let mut source = LirsReader::new(reader);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
+        if let Some(buffer) = &mut buffered {
+            buffer.push(event);
         }
-        InputFormat::Csv => {
-            use tracekit_formats::{CsvConfig, CsvReader};
-            let config = CsvConfig::key_only(); // Default to key-only, can be extended
-            let mut source = CsvReader::new(reader, config);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
+        Some(event)
+    });
+    let mut observers: Vec<_> = windows
+        .iter_mut()
+        .map(|slot| {
+            let window = slot
+                .as_mut()
+                .map(|(recorder, series)| recorder.observer(|w| series.push(w)));
+            WarmupFilter::new(
+                options.warmup,
+                options.trace_len,
+                (HitStats::default(), window),
+            )
+        })
+        .collect();
+    simulate_many_observed(
+        &mut caches,
+        &mut observers,
+        &mut events,
+        options.mode,
+        options.threads,
+    );
+    let online_stats: Vec<HitStats> = observers
+        .into_iter()
+        .map(|filter| filter.into_inner().0)
+        .collect();
+
+    let mut stats = vec![HitStats::default(); policies.len()];
+    let mut series = vec![Vec::new(); policies.len()];
+    for ((idx, policy_stats), slot) in online_idx.into_iter().zip(online_stats).zip(windows) {
+        stats[idx] = policy_stats;
+        if let Some((mut recorder, mut windows)) = slot {
+            windows.extend(recorder.finish());
+            series[idx] = windows;
         }
-        InputFormat::Cachelib => {
-            use tracekit_formats::CachelibReader;
-            let mut source = CachelibReader::with_defaults(reader);
-            let mut cache = SimpleLru::new(args.capacity);
-            simulate(&mut cache, &mut source)
+    }
+    if let Some(events) = buffered {
+        let optimal = OfflineTrace::from_source(&mut events.into_iter())
+            .belady_warm(capacity, options.warmup);
+        for (idx, policy) in policies.iter().enumerate() {
+            if policy.is_optimal() {
+                stats[idx] = optimal;
+            }
         }
-    };
-
-    println!("Simulation Results:");
-    println!("  Trace: {}", args.trace.display());
-    println!("  Cache capacity: {}", args.capacity);
-    println!("  Total requests: {}", stats.hits + stats.misses);
-    println!("  Hits: {}", stats.hits);
-    println!("  Misses: {}", stats.misses);
-    println!("  Hit rate: {:.2}%", stats.hit_rate() * 100.0);
-    println!("  Inserts: {}", stats.inserts);
+    }
 
-    Ok(())
+    ReplayResults {
+        stats,
+        series,
+        total_events,
+        total_requests,
+        unique_keys: keys.len(),
+    }
 }
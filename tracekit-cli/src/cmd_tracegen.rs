@@ -1,10 +1,18 @@
 //! `tracegen` command - Generate synthetic traces.
+//!
+//! Generates a single workload from the command-line options, or a phased
+//! `WorkloadSchedule` read from a JSON spec file with `--spec`. The spec
+//! carries its own operation mix, sizes, arrivals and TTLs, so `--spec`
+//! cannot be combined with the workload options.
 
 use clap::Args;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::PathBuf;
-use tracekit::{BoundedGenerator, Workload, WorkloadSpec};
+use tracekit::{
+    ArrivalModel, ArrivalProcess, BoundedGenerator, EventSource, OpMix, SizeDistribution,
+    SizeModel, TtlModel, Workload, WorkloadSchedule, WorkloadSpec,
+};
 
 #[derive(Args)]
 pub struct TracegenArgs {
@@ -36,6 +44,31 @@ pub struct TracegenArgs {
     #[arg(long, default_value = "0.9")]
     hot_prob: f64,
 
+    /// Operation mix, e.g. "read=0.9,update=0.1" or a YCSB preset such as "ycsb-a"
+    /// (non-read mixes need --format jsonl)
+    #[arg(long, default_value = "read=1")]
+    ops: OpMix,
+
+    /// Object size distribution written as event weights, e.g. "constant:size=4096",
+    /// "lognormal:mu=8,sigma=1.5" or "empirical:512=0.7,65536=0.3" (needs --format jsonl)
+    #[arg(long)]
+    sizes: Option<SizeDistribution>,
+
+    /// Give each key one stable size instead of a fresh size per request
+    #[arg(long, requires = "sizes")]
+    per_key_sizes: bool,
+
+    /// Arrival process stamping event timestamps, e.g. "poisson:rate=100",
+    /// "on_off:on_rate=1000,off_rate=10,mean_on=5,mean_off=60" or
+    /// "diurnal:rate=50,amplitude=0.8,period=86400" (needs --format jsonl)
+    #[arg(long)]
+    arrivals: Option<ArrivalProcess>,
+
+    /// Per-key TTL, e.g. "fixed:ttl=3600", "uniform:min=60,max=600" or "exponential:mean=300"
+    /// (needs --format jsonl)
+    #[arg(long)]
+    ttl: Option<TtlModel>,
+
     /// Output file (stdout if not specified)
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -43,6 +76,13 @@ pub struct TracegenArgs {
     /// Output format
     #[arg(short, long, value_enum, default_value = "key-only")]
     format: OutputFormat,
+
+    /// JSON workload schedule (universe, seed and phases) instead of the workload options
+    #[arg(long, conflicts_with_all = [
+        "workload", "universe", "count", "seed", "exponent", "hot_fraction", "hot_prob",
+        "ops", "sizes", "per_key_sizes", "arrivals", "ttl",
+    ])]
+    spec: Option<PathBuf>,
 }
 
 #[derive(Clone, Copy, clap::ValueEnum)]
@@ -80,13 +120,68 @@ pub fn run(args: TracegenArgs) -> Result<(), Box<dyn std::error::Error>> {
         },
     };
 
-    let spec = WorkloadSpec {
-        universe: args.universe,
-        workload,
-        seed: args.seed,
+    let schedule: Option<WorkloadSchedule> = match &args.spec {
+        Some(path) => {
+            let schedule: WorkloadSchedule =
+                serde_json::from_reader(BufReader::new(File::open(path)?))
+                    .map_err(|e| format!("invalid schedule {}: {e}", path.display()))?;
+            schedule
+                .validate()
+                .map_err(|e| format!("invalid schedule {}: {e}", path.display()))?;
+            Some(schedule)
+        }
+        None => None,
     };
 
-    let mut source = BoundedGenerator::new(spec.generator(), args.count);
+    if matches!(args.format, OutputFormat::KeyOnly) {
+        let (ops, sizes, arrivals, ttl) = match &schedule {
+            Some(s) => (
+                s.ops,
+                s.sizes.is_some(),
+                s.arrivals.is_some(),
+                s.ttl.is_some(),
+            ),
+            None => (
+                args.ops,
+                args.sizes.is_some(),
+                args.arrivals.is_some(),
+                args.ttl.is_some(),
+            ),
+        };
+        if !ops.is_read_only() {
+            return Err("an operation mix with writes or deletes requires --format jsonl".into());
+        }
+        let weighted_or_timed = [("sizes", sizes), ("arrivals", arrivals), ("ttl", ttl)];
+        if let Some((name, _)) = weighted_or_timed.iter().find(|(_, set)| *set) {
+            return Err(match schedule {
+                Some(_) => format!("the spec's \"{name}\" requires --format jsonl"),
+                None => format!("--{name} requires --format jsonl"),
+            }
+            .into());
+        }
+    }
+
+    let mut source: Box<dyn EventSource> = match &schedule {
+        Some(schedule) => Box::new(schedule.generator()),
+        None => {
+            let mut spec = WorkloadSpec::new(args.universe, workload, args.seed).with_ops(args.ops);
+            if let Some(distribution) = args.sizes.clone() {
+                spec = spec.with_sizes(if args.per_key_sizes {
+                    SizeModel::per_key(distribution)
+                } else {
+                    SizeModel::new(distribution)
+                });
+            }
+            if let Some(process) = args.arrivals {
+                spec = spec.with_arrivals(ArrivalModel::new(process));
+            }
+            if let Some(ttl) = args.ttl {
+                spec = spec.with_ttl(ttl);
+            }
+            Box::new(BoundedGenerator::new(spec.generator(), args.count))
+        }
+    };
+    let count = source.size_hint().unwrap_or(0);
 
     // Create output writer
     let writer: Box<dyn Write> = match &args.output {
@@ -97,7 +192,6 @@ pub fn run(args: TracegenArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.format {
         OutputFormat::KeyOnly => {
             let mut writer = tracekit_formats::KeyOnlyWriter::new(writer);
-            use tracekit::EventSource;
             while let Some(event) = source.next_event() {
                 writer.write_key(event.key)?;
             }
@@ -105,7 +199,6 @@ pub fn run(args: TracegenArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
         OutputFormat::Jsonl => {
             let mut writer = tracekit_formats::JsonlWriter::new(writer);
-            use tracekit::EventSource;
             while let Some(event) = source.next_event() {
                 writer.write_event(&event)?;
             }
@@ -114,7 +207,7 @@ pub fn run(args: TracegenArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     if let Some(path) = &args.output {
-        eprintln!("Generated {} events to {}", args.count, path.display());
+        eprintln!("Generated {count} events to {}", path.display());
     }
 
     Ok(())
@@ -3,15 +3,19 @@
 //! ## Commands
 //! - `tracegen`: Generate synthetic traces from workload specifications
 //! - `simulate`: Run cache simulation on a trace file
+//! - `mrc`: Sweep cache capacity and write miss-ratio curves
 //! - `rewrite`: Convert between trace formats
 //! - `render`: Render benchmark results to documentation
 
 use clap::{Parser, Subcommand};
 
+mod artifact;
+mod cmd_mrc;
 mod cmd_render;
 mod cmd_rewrite;
 mod cmd_simulate;
 mod cmd_tracegen;
+mod policy;
 
 #[derive(Parser)]
 #[command(name = "tracekit")]
@@ -25,8 +29,10 @@ struct Cli {
 enum Commands {
     /// Generate synthetic traces from workload specifications
     Tracegen(cmd_tracegen::TracegenArgs),
-    /// Run cache simulation on a trace file (placeholder)
+    /// Run cache simulation on a trace file
     Simulate(cmd_simulate::SimulateArgs),
+    /// Sweep cache capacity and write miss-ratio curves
+    Mrc(cmd_mrc::MrcArgs),
     /// Convert between trace formats
     Rewrite(cmd_rewrite::RewriteArgs),
     /// Render benchmark results to documentation
@@ -39,6 +45,7 @@ fn main() {
     let result = match cli.command {
         Commands::Tracegen(args) => cmd_tracegen::run(args),
         Commands::Simulate(args) => cmd_simulate::run(args),
+        Commands::Mrc(args) => cmd_mrc::run(args),
         Commands::Rewrite(args) => cmd_rewrite::run(args),
         Commands::Render(args) => cmd_render::run(args),
     };
@@ -0,0 +1,85 @@
+//! Policy selection shared by the simulation commands.
+//!
+//! Online policies are resolved through `tracekit::PolicyRegistry` from specs
+//! such as `lru` or `2q:kin=0.25`. `opt` selects the offline optimal, which has
+//! no online model; commands evaluate it through `tracekit::OfflineTrace`.
+
+use tracekit::registry::{BoxedCacheModel, RegistryError};
+use tracekit::{PolicyRegistry, PolicySpec};
+
+/// Spec id of the offline optimal policy.
+const OPT_ID: &str = "opt";
+
+/// A policy selected on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyChoice {
+    /// A registered online policy.
+    Online {
+        spec: PolicySpec,
+        display_name: String,
+    },
+    /// The offline optimal (Belady).
+    Optimal,
+}
+
+impl PolicyChoice {
+    /// Parse `spec` and validate it against `registry`.
+    pub fn parse(registry: &PolicyRegistry, spec: &str) -> Result<Self, String> {
+        let spec: PolicySpec = spec.parse().map_err(|e: RegistryError| e.to_string())?;
+        if spec.id == OPT_ID && spec.params.is_empty() {
+            return Ok(PolicyChoice::Optimal);
+        }
+        let display_name = registry.display_name(&spec).map_err(|e| match e {
+            RegistryError::UnknownPolicy(_) => {
+                let ids: Vec<&str> = registry.ids().chain([OPT_ID]).collect();
+                format!("{e} (available: {})", ids.join(", "))
+            }
+            _ => e.to_string(),
+        })?;
+        Ok(PolicyChoice::Online { spec, display_name })
+    }
+
+    /// Parse every spec, dropping duplicates while keeping the first occurrence.
+    pub fn parse_all(registry: &PolicyRegistry, specs: &[String]) -> Result<Vec<Self>, String> {
+        let mut choices: Vec<Self> = Vec::new();
+        for spec in specs {
+            let choice = Self::parse(registry, spec)?;
+            if !choices.contains(&choice) {
+                choices.push(choice);
+            }
+        }
+        Ok(choices)
+    }
+
+    /// Stable identifier used in CSV/JSON output (the normalized spec).
+    pub fn id(&self) -> String {
+        match self {
+            PolicyChoice::Online { spec, .. } => spec.to_string(),
+            PolicyChoice::Optimal => OPT_ID.to_string(),
+        }
+    }
+
+    /// Human-readable name.
+    pub fn display_name(&self) -> &str {
+        match self {
+            PolicyChoice::Online { display_name, .. } => display_name,
+            PolicyChoice::Optimal => "OPT",
+        }
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        matches!(self, PolicyChoice::Optimal)
+    }
+
+    /// Build an online model, or `None` for the offline optimal.
+    pub fn build(&self, registry: &PolicyRegistry, capacity: usize) -> Option<BoxedCacheModel> {
+        match self {
+            PolicyChoice::Online { spec, .. } => Some(
+                registry
+                    .build_spec(spec, capacity)
+                    .expect("spec validated when parsed"),
+            ),
+            PolicyChoice::Optimal => None,
+        }
+    }
+}
@@ -44,6 +44,8 @@ pub struct CachelibConfig {
     pub value_size_col: Option<usize>,
     /// Column index for operation type.
     pub op_col: Option<usize>,
+    /// Column index for TTL (same units as the timestamp column).
+    pub ttl_col: Option<usize>,
     /// Whether the first line is a header.
     pub has_header: bool,
 }
@@ -56,6 +58,7 @@ impl Default for CachelibConfig {
             key_size_col: Some(2),
             value_size_col: Some(3),
             op_col: None, // If not present, default to Get
+            ttl_col: Some(6),
             has_header: true,
         }
     }
@@ -161,6 +164,17 @@ impl<R: BufRead> EventSource for CachelibReader<R> {
                         None
                     };
 
+                    // Parse TTL (optional, 0 means "never expires", i.e. `None`)
+                    let ttl = if let Some(col) = self.config.ttl_col {
+                        if parts.len() > col {
+                            parts[col].trim().parse::<u64>().ok().filter(|&t| t > 0)
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    };
+
                     // Parse operation (optional)
                     let op = if let Some(col) = self.config.op_col {
                         if parts.len() > col && !parts[col].trim().is_empty() {
@@ -177,6 +191,7 @@ impl<R: BufRead> EventSource for CachelibReader<R> {
                         op,
                         weight,
                         ts,
+                        ttl,
                     };
                     if let Some(w) = weight {
                         event = event.with_weight(w);
@@ -211,11 +226,13 @@ mod tests {
         assert_eq!(e1.key, 12345);
         assert_eq!(e1.weight, Some(1024));
         assert_eq!(e1.ts, Some(1000));
+        assert_eq!(e1.ttl, Some(3600));
 
         let e2 = reader.next_event().unwrap();
         assert_eq!(e2.key, 67890);
         assert_eq!(e2.weight, Some(2048));
         assert_eq!(e2.ts, Some(2000));
+        assert_eq!(e2.ttl, Some(3600));
 
         assert!(reader.next_event().is_none());
     }
@@ -197,6 +197,7 @@ impl<R: BufRead> EventSource for CsvReader<R> {
                         op,
                         weight,
                         ts,
+                        ttl: None,
                     };
                     if let Some(w) = weight {
                         event = event.with_weight(w);
@@ -1,7 +1,9 @@
 //! JSONL trace format (one JSON object per line).
 //!
 //! This format supports the full Event model including operation type,
-//! weight, and timestamp.
+//! weight, and timestamp. `"insert"` covers every write: the Event model has
+//! no separate update op, so an overwrite of an existing key is written the
+//! same way as a fresh insert.
 //!
 //! ## Example
 //! ```text
@@ -9,6 +11,7 @@
 //! {"key":67890,"op":"insert"}
 //! {"key":12345,"op":"get","weight":100}
 //! {"key":11111,"op":"delete"}
+//! {"key":22222,"op":"insert","ts":1000,"ttl":60}
 //! ```
 
 use serde::{Deserialize, Serialize};
@@ -25,6 +28,8 @@ struct JsonEvent {
     weight: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     ts: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl: Option<u64>,
 }
 
 impl From<JsonEvent> for Event {
@@ -39,6 +44,7 @@ impl From<JsonEvent> for Event {
             op,
             weight: je.weight,
             ts: je.ts,
+            ttl: je.ttl,
         }
     }
 }
@@ -55,6 +61,7 @@ impl From<&Event> for JsonEvent {
             op,
             weight: e.weight,
             ts: e.ts,
+            ttl: e.ttl,
         }
     }
 }
@@ -167,7 +174,7 @@ mod tests {
 
     #[test]
     fn test_jsonl_reader_with_weight_ts() {
-        let data = r#"{"key":123,"weight":100,"ts":1000}"#;
+        let data = r#"{"key":123,"weight":100,"ts":1000,"ttl":60}"#;
         let cursor = Cursor::new(data);
         let mut reader = JsonlReader::new(cursor);
 
@@ -175,6 +182,7 @@ mod tests {
         assert_eq!(e.key, 123);
         assert_eq!(e.weight, Some(100));
         assert_eq!(e.ts, Some(1000));
+        assert_eq!(e.ttl, Some(60));
     }
 
     #[test]
@@ -113,8 +113,7 @@ impl TraceStats {
             100.0 * self.deletes as f64 / self.total_requests as f64
         );
 
-        if self.requests_with_weight > 0 {
-            let avg_size = self.total_bytes / self.requests_with_weight;
+        if let Some(avg_size) = self.total_bytes.checked_div(self.requests_with_weight) {
             println!("  Average object size: {} bytes", avg_size);
             println!("  Total data volume: {} bytes", self.total_bytes);
         }
@@ -0,0 +1,491 @@
+//! Arrival processes and TTLs for generated traces.
+//!
+//! ## Architecture
+//! An [`ArrivalModel`] stamps every generated event with a timestamp
+//! (`Event::ts`) drawn from an [`ArrivalProcess`], and a [`TtlModel`] gives
+//! every key a time-to-live (`Event::ttl`), so generated traces can drive
+//! [`simulate_timed`](crate::simulate_timed) and time-windowed statistics.
+//! Both use their own random streams, so they never change the keys.
+//!
+//! ## Processes
+//! Rates are events per trace time unit; timestamps are the arrival times
+//! rounded down, so several events can share a timestamp.
+//! - [`ArrivalProcess::Constant`]: evenly spaced arrivals
+//! - [`ArrivalProcess::Poisson`]: exponential inter-arrival times
+//! - [`ArrivalProcess::OnOff`]: a two-state Markov-modulated Poisson process
+//!   (MMPP) alternating between a busy and a quiet rate
+//! - [`ArrivalProcess::Diurnal`]: a Poisson process whose rate follows a
+//!   sinusoid, e.g. a daily cycle
+//!
+//! ## Bursts
+//! [`Workload::Bursty`](crate::Workload::Bursty) and
+//! [`Workload::FlashCrowd`](crate::Workload::FlashCrowd) only concentrate
+//! their keys during a burst. With a `burst_factor` above one, the arrival
+//! rate is also multiplied by it while the workload is in a burst, so the
+//! burst shows up in time as well.
+//!
+//! ## TTLs
+//! TTLs are assigned per key: a key's TTL depends only on the key and seed,
+//! so every Get and Insert of the key carries the same TTL. Deletes carry
+//! none.
+
+use std::f64::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Exp};
+use serde::{Deserialize, Serialize};
+
+use crate::hash::child_seed;
+use crate::sizes::ParamSpec;
+
+/// Separates the arrival stream from the key stream of the same seed.
+const ARRIVAL_SALT: u64 = 0x2f6b_93a1_c4d8_0e57;
+/// Separates TTL assignment from size assignment of the same seed.
+const TTL_SALT: u64 = 0x8d31_5ca7_46e2_b90b;
+
+/// Random process generating arrival times.
+///
+/// Serializes with a `kind` tag, e.g. `{"kind": "poisson", "rate": 100.0}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArrivalProcess {
+    /// One arrival every `1 / rate` time units.
+    Constant { rate: f64 },
+    /// Poisson arrivals at `rate`.
+    Poisson { rate: f64 },
+    /// Poisson arrivals at `on_rate` or `off_rate`, switching state after
+    /// exponentially distributed periods with means `mean_on` and `mean_off`.
+    /// A zero `off_rate` gives pure on/off bursts.
+    OnOff {
+        on_rate: f64,
+        off_rate: f64,
+        mean_on: f64,
+        mean_off: f64,
+    },
+    /// Poisson arrivals at `rate * (1 + amplitude * sin(2π t / period))`.
+    Diurnal {
+        rate: f64,
+        amplitude: f64,
+        period: f64,
+    },
+}
+
+impl ArrivalProcess {
+    /// Describe why the parameters are unusable, if they are.
+    fn invalid(&self) -> Option<&'static str> {
+        let positive = |v: f64| v.is_finite() && v > 0.0;
+        match *self {
+            ArrivalProcess::Constant { rate } | ArrivalProcess::Poisson { rate } => {
+                (!positive(rate)).then_some("rate must be positive")
+            }
+            ArrivalProcess::OnOff {
+                on_rate,
+                off_rate,
+                mean_on,
+                mean_off,
+            } => (!(positive(on_rate)
+                && off_rate.is_finite()
+                && off_rate >= 0.0
+                && positive(mean_on)
+                && positive(mean_off)))
+            .then_some("on_rate, mean_on and mean_off must be positive, off_rate non-negative"),
+            ArrivalProcess::Diurnal {
+                rate,
+                amplitude,
+                period,
+            } => (!(positive(rate) && (0.0..=1.0).contains(&amplitude) && positive(period)))
+                .then_some("rate and period must be positive, amplitude in [0, 1]"),
+        }
+    }
+}
+
+/// Parses `kind:param=value,...`, e.g. `poisson:rate=100`,
+/// `on_off:on_rate=1000,off_rate=10,mean_on=5,mean_off=60` or
+/// `diurnal:rate=50,amplitude=0.8,period=86400`.
+impl FromStr for ArrivalProcess {
+    type Err = ParseArrivalError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let err = |reason: String| ParseArrivalError(format!("arrival process '{spec}': {reason}"));
+        let parsed = ParamSpec::parse(spec).map_err(err)?;
+        let param = |name: &str| parsed.get(name).map_err(err);
+        let process = match parsed.kind.as_str() {
+            "constant" => ArrivalProcess::Constant {
+                rate: param("rate")?,
+            },
+            "poisson" => ArrivalProcess::Poisson {
+                rate: param("rate")?,
+            },
+            "on_off" | "onoff" | "mmpp" => ArrivalProcess::OnOff {
+                on_rate: param("on_rate")?,
+                off_rate: param("off_rate")?,
+                mean_on: param("mean_on")?,
+                mean_off: param("mean_off")?,
+            },
+            "diurnal" => ArrivalProcess::Diurnal {
+                rate: param("rate")?,
+                amplitude: param("amplitude")?,
+                period: param("period")?,
+            },
+            _ => return Err(err("unknown arrival process".to_string())),
+        };
+        match process.invalid() {
+            Some(reason) => Err(err(reason.to_string())),
+            None => Ok(process),
+        }
+    }
+}
+
+/// Arrival process plus its coupling to workload bursts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArrivalModel {
+    /// Process generating arrival times.
+    pub process: ArrivalProcess,
+    /// Rate multiplier while a Bursty or FlashCrowd workload is in a burst;
+    /// one leaves the rate unchanged.
+    #[serde(default = "ArrivalModel::default_burst_factor")]
+    pub burst_factor: f64,
+}
+
+impl ArrivalModel {
+    /// Arrivals from `process`, independent of workload bursts.
+    pub fn new(process: ArrivalProcess) -> Self {
+        Self {
+            process,
+            burst_factor: Self::default_burst_factor(),
+        }
+    }
+
+    /// Speed arrivals up by `factor` during workload bursts.
+    pub fn with_burst_factor(mut self, factor: f64) -> Self {
+        self.burst_factor = factor;
+        self
+    }
+
+    fn default_burst_factor() -> f64 {
+        1.0
+    }
+}
+
+/// Distribution of per-key TTLs, in trace time units.
+///
+/// TTLs are always positive: a zero TTL would expire entries immediately in
+/// [`simulate_timed`](crate::simulate_timed), while many trace formats use 0
+/// for "never expires".
+///
+/// Serializes with a `kind` tag, e.g. `{"kind": "fixed", "ttl": 3600}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TtlModel {
+    /// Every key gets `ttl`.
+    Fixed { ttl: u64 },
+    /// Uniform in `[min, max]`.
+    Uniform { min: u64, max: u64 },
+    /// Exponential with mean `mean`, rounded up to at least one.
+    Exponential { mean: f64 },
+}
+
+impl TtlModel {
+    /// Describe why the parameters are unusable, if they are.
+    fn invalid(&self) -> Option<&'static str> {
+        match *self {
+            TtlModel::Fixed { ttl } => (ttl == 0).then_some("ttl must be positive"),
+            TtlModel::Uniform { min, max } => {
+                if min == 0 {
+                    Some("min must be positive")
+                } else {
+                    (min > max).then_some("min exceeds max")
+                }
+            }
+            TtlModel::Exponential { mean } => {
+                (!(mean.is_finite() && mean > 0.0)).then_some("mean must be positive")
+            }
+        }
+    }
+
+    /// # Panics
+    /// Panics if the parameters are invalid.
+    pub(crate) fn validate(&self) {
+        if let Some(reason) = self.invalid() {
+            panic!("invalid TTL model {self:?}: {reason}");
+        }
+    }
+
+    /// TTL of `key` under `seed`.
+    pub(crate) fn ttl(&self, seed: u64, key: u64) -> u64 {
+        let mut rng = SmallRng::seed_from_u64(child_seed(seed ^ TTL_SALT, key));
+        match *self {
+            TtlModel::Fixed { ttl } => ttl,
+            TtlModel::Uniform { min, max } => rng.random_range(min..=max),
+            TtlModel::Exponential { mean } => {
+                let ttl: f64 = Exp::new(1.0 / mean).unwrap().sample(&mut rng);
+                (ttl.ceil() as u64).max(1)
+            }
+        }
+    }
+}
+
+/// Parses `fixed:ttl=3600`, `uniform:min=60,max=600` or `exponential:mean=300`.
+impl FromStr for TtlModel {
+    type Err = ParseArrivalError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let err = |reason: String| ParseArrivalError(format!("TTL model '{spec}': {reason}"));
+        let parsed = ParamSpec::parse(spec).map_err(err)?;
+        let ttl = |name: &str| parsed.integer(name, u64::MAX).map_err(err);
+        let model = match parsed.kind.as_str() {
+            "fixed" => TtlModel::Fixed { ttl: ttl("ttl")? },
+            "uniform" => TtlModel::Uniform {
+                min: ttl("min")?,
+                max: ttl("max")?,
+            },
+            "exponential" => TtlModel::Exponential {
+                mean: parsed.get("mean").map_err(err)?,
+            },
+            _ => return Err(err("unknown TTL model".to_string())),
+        };
+        match model.invalid() {
+            Some(reason) => Err(err(reason.to_string())),
+            None => Ok(model),
+        }
+    }
+}
+
+/// Error from parsing an [`ArrivalProcess`] or [`TtlModel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseArrivalError(String);
+
+impl fmt::Display for ParseArrivalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseArrivalError {}
+
+/// Arrival clock of a generator.
+#[derive(Debug, Clone)]
+pub(crate) struct ArrivalClock {
+    model: ArrivalModel,
+    rng: SmallRng,
+    /// Time of the last arrival.
+    now: f64,
+    /// On/off state and the time it ends.
+    on: bool,
+    state_until: f64,
+}
+
+impl ArrivalClock {
+    /// # Panics
+    /// Panics if the process's parameters or the burst factor are invalid.
+    pub(crate) fn new(model: ArrivalModel, seed: u64) -> Self {
+        if let Some(reason) = model.process.invalid() {
+            panic!("invalid arrival process {:?}: {reason}", model.process);
+        }
+        assert!(
+            model.burst_factor.is_finite() && model.burst_factor > 0.0,
+            "invalid burst factor {}",
+            model.burst_factor
+        );
+        let mut clock = Self {
+            model,
+            rng: SmallRng::seed_from_u64(seed ^ ARRIVAL_SALT),
+            now: 0.0,
+            on: true,
+            state_until: 0.0,
+        };
+        if let ArrivalProcess::OnOff { mean_on, .. } = model.process {
+            clock.state_until = clock.exponential(1.0 / mean_on);
+        }
+        clock
+    }
+
+    /// Timestamp of the next arrival.
+    pub(crate) fn next_ts(&mut self, in_burst: bool) -> u64 {
+        let factor = if in_burst {
+            self.model.burst_factor
+        } else {
+            1.0
+        };
+        match self.model.process {
+            ArrivalProcess::Constant { rate } => self.now += 1.0 / (rate * factor),
+            ArrivalProcess::Poisson { rate } => self.now += self.exponential(rate * factor),
+            ArrivalProcess::OnOff {
+                on_rate,
+                off_rate,
+                mean_on,
+                mean_off,
+            } => loop {
+                // Exponential gaps are memoryless, so a gap cut short by a
+                // state change is simply redrawn at the new rate.
+                let rate = if self.on { on_rate } else { off_rate } * factor;
+                let gap = if rate > 0.0 {
+                    self.exponential(rate)
+                } else {
+                    f64::INFINITY
+                };
+                if self.now + gap <= self.state_until {
+                    self.now += gap;
+                    break;
+                }
+                self.now = self.state_until;
+                self.on = !self.on;
+                let mean = if self.on { mean_on } else { mean_off };
+                self.state_until = self.now + self.exponential(1.0 / mean);
+            },
+            ArrivalProcess::Diurnal {
+                rate,
+                amplitude,
+                period,
+            } => {
+                // Thinning: candidates at the peak rate, accepted with
+                // probability rate(t) / peak.
+                let peak = rate * (1.0 + amplitude) * factor;
+                loop {
+                    self.now += self.exponential(peak);
+                    let current = rate * (1.0 + amplitude * (TAU * self.now / period).sin());
+                    if self.rng.random::<f64>() * peak < current * factor {
+                        break;
+                    }
+                }
+            }
+        }
+        self.now as u64
+    }
+
+    fn exponential(&mut self, rate: f64) -> f64 {
+        Exp::new(rate).unwrap().sample(&mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Op;
+    use crate::source::EventSource;
+    use crate::workload::{Workload, WorkloadSpec};
+
+    fn timestamps(model: ArrivalModel, count: usize) -> Vec<u64> {
+        let mut clock = ArrivalClock::new(model, 1);
+        (0..count).map(|_| clock.next_ts(false)).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "poisson:rate=100".parse::<ArrivalProcess>().unwrap(),
+            ArrivalProcess::Poisson { rate: 100.0 }
+        );
+        assert_eq!(
+            "mmpp:on_rate=10,off_rate=0,mean_on=5,mean_off=50"
+                .parse::<ArrivalProcess>()
+                .unwrap(),
+            ArrivalProcess::OnOff {
+                on_rate: 10.0,
+                off_rate: 0.0,
+                mean_on: 5.0,
+                mean_off: 50.0
+            }
+        );
+        assert!("poisson:rate=0".parse::<ArrivalProcess>().is_err());
+        assert!(
+            "diurnal:rate=1,amplitude=2,period=10"
+                .parse::<ArrivalProcess>()
+                .is_err()
+        );
+        assert_eq!(
+            "uniform:min=60,max=600".parse::<TtlModel>().unwrap(),
+            TtlModel::Uniform { min: 60, max: 600 }
+        );
+        assert!("fixed:ttl=1.5".parse::<TtlModel>().is_err());
+        assert!("fixed:ttl=0".parse::<TtlModel>().is_err());
+        assert!("uniform:min=0,max=10".parse::<TtlModel>().is_err());
+
+        let model: ArrivalModel =
+            serde_json::from_str(r#"{"process": {"kind": "constant", "rate": 2.0}}"#).unwrap();
+        assert_eq!(
+            model,
+            ArrivalModel::new(ArrivalProcess::Constant { rate: 2.0 })
+        );
+    }
+
+    #[test]
+    fn test_processes_follow_their_rate() {
+        let constant = timestamps(ArrivalModel::new(ArrivalProcess::Constant { rate: 0.5 }), 4);
+        assert_eq!(constant, vec![2, 4, 6, 8]);
+
+        let poisson = timestamps(
+            ArrivalModel::new(ArrivalProcess::Poisson { rate: 10.0 }),
+            10_000,
+        );
+        let span = *poisson.last().unwrap() as f64;
+        assert!((950.0..1_050.0).contains(&span), "{span}");
+
+        // Pure on/off: half the time is spent off, and gaps show up as idle units.
+        let on_off = timestamps(
+            ArrivalModel::new(ArrivalProcess::OnOff {
+                on_rate: 10.0,
+                off_rate: 0.0,
+                mean_on: 10.0,
+                mean_off: 10.0,
+            }),
+            10_000,
+        );
+        let span = *on_off.last().unwrap() as f64;
+        assert!((1_700.0..2_300.0).contains(&span), "{span}");
+        assert!(on_off.windows(2).any(|pair| pair[1] - pair[0] >= 10));
+
+        // The first half of each diurnal period is the busy one.
+        let diurnal = timestamps(
+            ArrivalModel::new(ArrivalProcess::Diurnal {
+                rate: 10.0,
+                amplitude: 0.9,
+                period: 100.0,
+            }),
+            10_000,
+        );
+        let busy = diurnal.iter().filter(|&&ts| ts % 100 < 50).count();
+        assert!(busy > 7_500, "{busy}");
+    }
+
+    #[test]
+    fn test_generator_stamps_events() {
+        let flash = Workload::FlashCrowd {
+            base_exponent: 1.0,
+            flash_prob: 1.0,
+            flash_duration: 1_000_000,
+            flash_keys: 10,
+            flash_intensity: 10.0,
+        };
+        let arrivals = ArrivalModel::new(ArrivalProcess::Constant { rate: 1.0 });
+        let span = |model: ArrivalModel| {
+            let spec = WorkloadSpec::new(1_000, flash.clone(), 3).with_arrivals(model);
+            let mut source = spec.generator();
+            (0..1_000)
+                .filter_map(|_| source.next_event())
+                .last()
+                .unwrap()
+                .ts
+        };
+        assert_eq!(span(arrivals), Some(1_000));
+        // Every event is in the flash crowd, so time runs four times faster.
+        assert_eq!(span(arrivals.with_burst_factor(4.0)), Some(250));
+
+        let spec = WorkloadSpec::new(50, Workload::Uniform, 3)
+            .with_ops("read=1,delete=1".parse().unwrap())
+            .with_ttl(TtlModel::Uniform { min: 1, max: 1_000 });
+        let mut source = spec.generator();
+        let mut seen = std::collections::HashMap::new();
+        for event in (0..1_000).filter_map(|_| source.next_event()) {
+            if event.op == Op::Delete {
+                assert_eq!(event.ttl, None);
+            } else {
+                let ttl = event.ttl.unwrap();
+                assert_eq!(*seen.entry(event.key).or_insert(ttl), ttl);
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! ## Architecture
 //! Events represent individual cache operations in a trace. Each event has a key
-//! and an operation type, with optional weight and timestamp for future extensions.
+//! and an operation type, with optional weight, timestamp and TTL.
 //!
 //! ## Key Components
 //! - [`Event`]: A single cache access event
@@ -19,6 +19,11 @@ pub struct Event {
     pub weight: Option<u32>,
     /// Optional timestamp for TTL/time-aware policies (v0.2+).
     pub ts: Option<u64>,
+    /// Optional time-to-live for the inserted entry, in the same units as `ts`.
+    ///
+    /// `None` means the entry never expires. `Some(0)` expires immediately;
+    /// trace parsers whose formats use 0 for "no TTL" map it to `None`.
+    pub ttl: Option<u64>,
 }
 
 impl Event {
@@ -30,6 +35,7 @@ impl Event {
             op: Op::Get,
             weight: None,
             ts: None,
+            ttl: None,
         }
     }
 
@@ -41,6 +47,7 @@ impl Event {
             op: Op::Insert,
             weight: None,
             ts: None,
+            ttl: None,
         }
     }
 
@@ -52,6 +59,7 @@ impl Event {
             op: Op::Delete,
             weight: None,
             ts: None,
+            ttl: None,
         }
     }
 
@@ -68,6 +76,13 @@ impl Event {
         self.ts = Some(ts);
         self
     }
+
+    /// Set the time-to-live for this event.
+    #[inline]
+    pub const fn with_ttl(mut self, ttl: u64) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
 }
 
 /// Cache operation type.
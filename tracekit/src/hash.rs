@@ -0,0 +1,19 @@
+//! SplitMix64 mixing shared by seed derivation and key sampling.
+
+/// Golden-ratio increment of SplitMix64.
+const GOLDEN: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// SplitMix64 output for state `x`: a fast, well-distributed 64-bit mix.
+#[inline]
+pub(crate) fn mix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Independent seed for child `idx` of `seed`, e.g. a mixture component or a key.
+#[inline]
+pub(crate) fn child_seed(seed: u64, idx: u64) -> u64 {
+    mix64(seed ^ idx.wrapping_mul(GOLDEN))
+}
@@ -6,8 +6,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::metrics;
+use crate::simulator::Warmup;
+
 /// Version of the benchmark results schema.
-pub const SCHEMA_VERSION: &str = "1.0.0";
+pub const SCHEMA_VERSION: &str = "1.1.0";
 
 /// Complete benchmark run artifact.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +55,31 @@ pub struct BenchmarkConfig {
     pub operations: usize,
     /// Random seed used for reproducibility.
     pub seed: u64,
+    /// Warmup excluded from the statistics, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<WarmupConfig>,
+}
+
+/// Warmup policy recorded in [`BenchmarkConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WarmupConfig {
+    /// The first `events` events.
+    Events { events: u64 },
+    /// The leading `fraction` of the trace.
+    Fraction { fraction: f64 },
+    /// Up to the first eviction.
+    UntilFull,
+}
+
+impl From<Warmup> for WarmupConfig {
+    fn from(warmup: Warmup) -> Self {
+        match warmup {
+            Warmup::Events(events) => WarmupConfig::Events { events },
+            Warmup::Fraction(fraction) => WarmupConfig::Fraction { fraction },
+            Warmup::UntilFull => WarmupConfig::UntilFull,
+        }
+    }
 }
 
 /// A single result row in the benchmark matrix.
@@ -67,12 +95,19 @@ pub struct ResultRow {
     pub workload_name: String,
     /// Benchmark case type (e.g., "hit_rate", "comprehensive", "scan_resistance").
     pub case_id: String,
+    /// Cache capacity this row was run at; for `mrc` rows, the largest
+    /// capacity on the curve.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+    /// Seed of this row, when it differs across rows (e.g. sweeps).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
     /// Detailed metrics for this result.
     pub metrics: Metrics,
 }
 
 /// Metrics collected during a benchmark run.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Metrics {
     /// Hit/miss statistics.
     pub hit_stats: Option<HitStats>,
@@ -86,6 +121,9 @@ pub struct Metrics {
     pub scan_resistance: Option<ScanResistanceStats>,
     /// Adaptation speed results.
     pub adaptation: Option<AdaptationStats>,
+    /// Miss-ratio curve.
+    #[serde(default)]
+    pub mrc: Option<MrcStats>,
 }
 
 /// Hit/miss statistics.
@@ -97,6 +135,52 @@ pub struct HitStats {
     pub updates: u64,
     pub hit_rate: f64,
     pub miss_rate: f64,
+    /// Bytes served from cache (weighted simulations only).
+    #[serde(default)]
+    pub hit_bytes: u64,
+    /// Bytes fetched on miss (weighted simulations only).
+    #[serde(default)]
+    pub miss_bytes: u64,
+    /// Fraction of requested bytes served from cache.
+    #[serde(default)]
+    pub byte_hit_rate: f64,
+    /// Entries removed because their TTL elapsed.
+    #[serde(default)]
+    pub expirations: u64,
+    /// Misses and writes that were not stored (write-through/around writes,
+    /// or misses MIN declines to admit).
+    #[serde(default)]
+    pub bypasses: u64,
+    /// Misses on the first reference to a key.
+    #[serde(default)]
+    pub compulsory_misses: u64,
+    /// Misses on previously referenced, non-invalidated keys.
+    #[serde(default)]
+    pub capacity_misses: u64,
+    /// Misses on keys invalidated by a Delete (or TTL expiry).
+    #[serde(default)]
+    pub delete_misses: u64,
+}
+
+impl From<&metrics::HitStats> for HitStats {
+    fn from(stats: &metrics::HitStats) -> Self {
+        Self {
+            hits: stats.hits,
+            misses: stats.misses,
+            inserts: stats.inserts,
+            updates: stats.updates,
+            hit_rate: stats.hit_rate(),
+            miss_rate: stats.miss_rate(),
+            hit_bytes: stats.hit_bytes,
+            miss_bytes: stats.miss_bytes,
+            byte_hit_rate: stats.byte_hit_rate(),
+            expirations: stats.expirations,
+            bypasses: stats.bypasses,
+            compulsory_misses: stats.compulsory_misses,
+            capacity_misses: stats.capacity_misses,
+            delete_misses: stats.delete_misses,
+        }
+    }
 }
 
 /// Throughput measurements.
@@ -112,6 +196,17 @@ pub struct ThroughputStats {
     pub inserts_per_sec: f64,
 }
 
+impl From<&metrics::ThroughputStats> for ThroughputStats {
+    fn from(stats: &metrics::ThroughputStats) -> Self {
+        Self {
+            duration_ms: stats.total_duration.as_secs_f64() * 1_000.0,
+            ops_per_sec: stats.ops_per_sec,
+            gets_per_sec: stats.gets_per_sec,
+            inserts_per_sec: stats.inserts_per_sec,
+        }
+    }
+}
+
 /// Latency distribution statistics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LatencyStats {
@@ -131,6 +226,20 @@ pub struct LatencyStats {
     pub mean_ns: u64,
 }
 
+impl From<&metrics::LatencyStats> for LatencyStats {
+    fn from(stats: &metrics::LatencyStats) -> Self {
+        Self {
+            sample_count: stats.sample_count,
+            min_ns: duration_to_nanos(stats.min),
+            p50_ns: duration_to_nanos(stats.p50),
+            p95_ns: duration_to_nanos(stats.p95),
+            p99_ns: duration_to_nanos(stats.p99),
+            max_ns: duration_to_nanos(stats.max),
+            mean_ns: duration_to_nanos(stats.mean),
+        }
+    }
+}
+
 /// Eviction behavior statistics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvictionStats {
@@ -140,6 +249,15 @@ pub struct EvictionStats {
     pub evictions_per_insert: f64,
 }
 
+impl From<&metrics::EvictionStats> for EvictionStats {
+    fn from(stats: &metrics::EvictionStats) -> Self {
+        Self {
+            total_evictions: stats.total_evictions,
+            evictions_per_insert: stats.evictions_per_insert,
+        }
+    }
+}
+
 /// Scan resistance measurement results.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResistanceStats {
@@ -153,6 +271,17 @@ pub struct ScanResistanceStats {
     pub resistance_score: f64,
 }
 
+impl From<&metrics::ScanResistanceResult> for ScanResistanceStats {
+    fn from(result: &metrics::ScanResistanceResult) -> Self {
+        Self {
+            baseline_hit_rate: result.baseline_hit_rate,
+            scan_hit_rate: result.scan_hit_rate,
+            recovery_hit_rate: result.recovery_hit_rate,
+            resistance_score: result.resistance_score,
+        }
+    }
+}
+
 /// Adaptation speed measurement results.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdaptationStats {
@@ -164,6 +293,70 @@ pub struct AdaptationStats {
     pub ops_to_80_percent: usize,
 }
 
+impl From<&metrics::AdaptationResult> for AdaptationStats {
+    fn from(result: &metrics::AdaptationResult) -> Self {
+        Self {
+            stable_hit_rate: result.stable_hit_rate,
+            ops_to_50_percent: result.ops_to_50_percent,
+            ops_to_80_percent: result.ops_to_80_percent,
+        }
+    }
+}
+
+/// Miss ratio as a function of cache capacity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MrcStats {
+    /// How the curve was computed ("exact", "shards", "counter_stacks", or a policy replay).
+    pub method: String,
+    /// Curve points in ascending capacity order.
+    pub points: Vec<MrcPoint>,
+}
+
+/// A single miss-ratio curve point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MrcPoint {
+    /// Cache capacity in entries.
+    pub capacity: usize,
+    /// Miss ratio at this capacity.
+    pub miss_ratio: f64,
+}
+
+impl From<&metrics::BenchmarkResult> for ResultRow {
+    /// Row for the `comprehensive` case, with ids derived from the display names.
+    fn from(result: &metrics::BenchmarkResult) -> Self {
+        Self {
+            policy_id: slug(&result.policy_name),
+            policy_name: result.policy_name.clone(),
+            workload_id: slug(&result.workload_name),
+            workload_name: result.workload_name.clone(),
+            case_id: "comprehensive".to_string(),
+            capacity: Some(result.capacity),
+            seed: None,
+            metrics: Metrics {
+                hit_stats: Some((&result.hit_stats).into()),
+                throughput: Some((&result.throughput).into()),
+                latency: Some((&result.latency).into()),
+                eviction: Some((&result.eviction).into()),
+                ..Metrics::default()
+            },
+        }
+    }
+}
+
+/// Lowercase identifier with runs of non-alphanumerics collapsed to `_`
+/// ("S3-FIFO" -> "s3_fifo", "Zipfian 1.0" -> "zipfian_1_0").
+fn slug(name: &str) -> String {
+    let mut id = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    id.trim_end_matches('_').to_string()
+}
+
 impl BenchmarkArtifact {
     /// Create a new benchmark artifact with metadata.
     pub fn new(metadata: RunMetadata) -> Self {
@@ -5,9 +5,25 @@
 //! - [`EventSource`]: trait for trace streams or generators
 //! - [`CacheModel`]: minimal cache interface for simulation
 //! - [`simulate`]: core simulation loop
+//! - [`simulate_weighted`]: size-aware simulation with byte hit ratio
+//! - [`simulate_timed`]: time-aware simulation with TTL expiration
+//! - [`simulate_warm`]: simulation that excludes a cache-filling [`Warmup`]
+//! - [`simulate_many`]: one pass over a trace for several models, optionally threaded
+//! - [`simulate_observed`]: generic driver feeding a [`SimObserver`]
+//! - [`simulate_windowed`]: per-window statistics over the course of a trace
 //! - [`workload`]: 16+ synthetic workload generators
+//! - [`mixture`]: weighted mixtures of workloads over shared or offset key spaces
+//! - [`schedule`]: phased workloads whose parameters change over time
+//! - [`op_mix`]: read/update/insert/delete mixes and YCSB presets for generators
+//! - [`sizes`]: object size distributions for generated events
+//! - [`arrival`]: arrival processes and per-key TTLs for generated events
+//! - [`policies`]: reference cache policies (LRU, ARC, S3-FIFO, ...)
+//! - [`opt`]: offline optimal (Bélády) upper bound
+//! - [`timeseries`]: windowed time-series statistics
+//! - [`sweep`]: parallel policy × workload × capacity × seed sweeps
 //! - [`metrics`]: benchmark metrics collection
-//! - [`registry`]: policy/workload registries
+//! - [`mrc`]: one-pass miss-ratio curves (exact, SHARDS, counter stacks)
+//! - [`registry`]: policy/workload registries (`PolicyRegistry` builds policies by name)
 //! - [`json_results`]: JSON serialization for results
 //!
 //! ## Example
@@ -15,11 +31,7 @@
 //! use tracekit::{simulate, CacheModel, WorkloadSpec, Workload, BoundedGenerator};
 //!
 //! // Create a workload generator
-//! let spec = WorkloadSpec {
-//!     universe: 10_000,
-//!     workload: Workload::Zipfian { exponent: 1.0 },
-//!     seed: 42,
-//! };
+//! let spec = WorkloadSpec::new(10_000, Workload::Zipfian { exponent: 1.0 }, 42);
 //! let mut source = BoundedGenerator::new(spec.generator(), 100_000);
 //!
 //! // Simulate with your cache implementation
@@ -28,20 +40,45 @@
 //! println!("Hit rate: {:.2}%", stats.hit_rate() * 100.0);
 //! ```
 
+pub mod arrival;
 pub mod event;
+mod hash;
 pub mod json_results;
 pub mod metrics;
+pub mod mixture;
 pub mod model;
+pub mod mrc;
+pub mod observer;
+pub mod op_mix;
+pub mod opt;
+pub mod policies;
 pub mod registry;
+pub mod schedule;
 pub mod simulator;
+pub mod sizes;
 pub mod source;
+pub mod sweep;
+pub mod timeseries;
 pub mod workload;
 
 // Re-exports for convenience
+pub use arrival::{ArrivalModel, ArrivalProcess, TtlModel};
 pub use event::{Event, Op};
+pub use mixture::{MixtureComponent, MixtureGenerator};
 pub use model::CacheModel;
-pub use simulator::{simulate, simulate_explicit};
+pub use observer::{MissKind, Outcome, SimObserver, WarmupFilter};
+pub use op_mix::{OpMix, Ycsb};
+pub use opt::OfflineTrace;
+pub use registry::{PolicyRegistry, PolicySpec};
+pub use schedule::{Phase, ScheduleError, ScheduleGenerator, WorkloadSchedule};
+pub use simulator::{
+    Expiry, OnMiss, ReplayMode, Warmup, WritePolicy, simulate, simulate_explicit, simulate_many,
+    simulate_many_observed, simulate_many_threaded, simulate_observed, simulate_timed,
+    simulate_warm, simulate_weighted, simulate_windowed, simulate_with_evictions,
+};
+pub use sizes::{SizeDistribution, SizeModel};
 pub use source::EventSource;
+pub use sweep::Sweep;
 pub use workload::{BoundedGenerator, Workload, WorkloadGenerator, WorkloadSpec};
 
 // Note: for_each_policy macro is automatically exported at crate root via #[macro_export]
@@ -7,8 +7,13 @@
 //! - Eviction behavior
 //! - Adaptation speed
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::event::Event;
+use crate::model::CacheModel;
+use crate::observer::{MissClassifier, MissKind, Outcome, SimObserver};
+use crate::simulator::{Replay, ReplayMode};
+use crate::source::EventSource;
 use crate::workload::WorkloadSpec;
 
 // ============================================================================
@@ -54,12 +59,33 @@ impl BenchmarkResult {
 }
 
 /// Hit/miss statistics.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct HitStats {
     pub hits: u64,
     pub misses: u64,
     pub inserts: u64,
     pub updates: u64,
+    /// Bytes served from cache (sum of hit weights).
+    pub hit_bytes: u64,
+    /// Bytes fetched on miss (sum of miss weights).
+    pub miss_bytes: u64,
+    /// Entries removed because their TTL elapsed (not capacity evictions).
+    pub expirations: u64,
+    /// Entries displaced by capacity pressure, as reported by the model.
+    pub evictions: u64,
+    /// Misses and writes that were not stored: writes routed around the
+    /// cache by [`ReplayMode::writes`], and misses MIN declines to admit
+    /// because the resident objects are all used sooner.
+    pub bypasses: u64,
+    /// Misses on the first reference to a key. This and the next two
+    /// counters stay zero unless the driver classifies misses
+    /// ([`ReplayMode::classify_misses`]).
+    pub compulsory_misses: u64,
+    /// Misses on previously referenced keys that were not invalidated.
+    pub capacity_misses: u64,
+    /// Misses on keys invalidated since their last reference (explicit
+    /// Delete, or TTL expiry in timed simulation).
+    pub delete_misses: u64,
 }
 
 impl HitStats {
@@ -81,6 +107,71 @@ impl HitStats {
     pub fn total_ops(&self) -> u64 {
         self.hits + self.misses
     }
+
+    /// Fraction of requested bytes served from cache.
+    ///
+    /// Only meaningful for weighted simulations; unit-weight runs leave
+    /// `hit_bytes`/`miss_bytes` at zero and report 0.0.
+    #[inline]
+    pub fn byte_hit_rate(&self) -> f64 {
+        let total = self.total_bytes();
+        if total == 0 {
+            0.0
+        } else {
+            self.hit_bytes as f64 / total as f64
+        }
+    }
+
+    #[inline]
+    pub fn byte_miss_rate(&self) -> f64 {
+        1.0 - self.byte_hit_rate()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.hit_bytes + self.miss_bytes
+    }
+
+    /// Count one miss of the given kind in the classification counters.
+    ///
+    /// Only increments the `*_misses` breakdown, not `misses` itself.
+    #[inline]
+    pub fn record_miss(&mut self, kind: MissKind) {
+        match kind {
+            MissKind::Compulsory => self.compulsory_misses += 1,
+            MissKind::Capacity => self.capacity_misses += 1,
+            MissKind::Delete => self.delete_misses += 1,
+        }
+    }
+
+    /// Derive eviction statistics from the recorded counts.
+    pub fn eviction_stats(&self) -> EvictionStats {
+        EvictionStats {
+            total_evictions: self.evictions,
+            evictions_per_insert: if self.inserts == 0 {
+                0.0
+            } else {
+                self.evictions as f64 / self.inserts as f64
+            },
+        }
+    }
+}
+
+/// Accumulate counts, e.g. across trace segments replayed separately.
+impl std::ops::AddAssign for HitStats {
+    fn add_assign(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.inserts += other.inserts;
+        self.updates += other.updates;
+        self.hit_bytes += other.hit_bytes;
+        self.miss_bytes += other.miss_bytes;
+        self.expirations += other.expirations;
+        self.evictions += other.evictions;
+        self.bypasses += other.bypasses;
+        self.compulsory_misses += other.compulsory_misses;
+        self.capacity_misses += other.capacity_misses;
+        self.delete_misses += other.delete_misses;
+    }
 }
 
 /// Throughput measurements.
@@ -97,6 +188,11 @@ pub struct ThroughputStats {
 }
 
 impl ThroughputStats {
+    /// Throughput with `hits + misses + inserts` as the operation count.
+    ///
+    /// Only right when every insert is a separate operation; read-through
+    /// fills would be counted twice. Prefer
+    /// [`from_operations`](Self::from_operations).
     pub fn from_counts(hits: u64, misses: u64, inserts: u64, duration: Duration) -> Self {
         let secs = duration.as_secs_f64();
         if secs == 0.0 {
@@ -110,6 +206,21 @@ impl ThroughputStats {
             inserts_per_sec: inserts as f64 / secs,
         }
     }
+
+    /// Throughput of `operations` replayed events, of which `gets` were
+    /// lookups, that made `inserts` inserts into the model.
+    pub fn from_operations(operations: u64, gets: u64, inserts: u64, duration: Duration) -> Self {
+        let secs = duration.as_secs_f64();
+        if secs == 0.0 {
+            return Self::default();
+        }
+        Self {
+            total_duration: duration,
+            ops_per_sec: operations as f64 / secs,
+            gets_per_sec: gets as f64 / secs,
+            inserts_per_sec: inserts as f64 / secs,
+        }
+    }
 }
 
 /// Latency distribution (collected via sampling).
@@ -213,6 +324,29 @@ impl LatencySampler {
     }
 }
 
+/// Offers the time between consecutive events of a replay to a sampler.
+///
+/// The clock restarts after each reported eviction, so eviction bookkeeping
+/// is not charged to the next event.
+struct LatencyObserver {
+    sampler: LatencySampler,
+    last: Instant,
+}
+
+impl SimObserver for LatencyObserver {
+    #[inline]
+    fn on_event(&mut self, _index: u64, _event: &Event, _outcome: Outcome) {
+        let now = Instant::now();
+        self.sampler.record(now - self.last);
+        self.last = now;
+    }
+
+    #[inline]
+    fn on_evict(&mut self, _index: u64, _key: u64) {
+        self.last = Instant::now();
+    }
+}
+
 // ============================================================================
 // Benchmark Runner
 // ============================================================================
@@ -243,25 +377,162 @@ impl Default for BenchmarkConfig {
             capacity: 4096,
             operations: 100_000,
             warmup_ops: 10_000,
-            workload: WorkloadSpec {
-                universe: 16_384,
-                workload: crate::workload::Workload::Zipfian { exponent: 1.0 },
-                seed: 42,
-            },
+            workload: WorkloadSpec::default(),
             latency_sample_rate: 100,
             max_latency_samples: 10_000,
         }
     }
 }
 
+/// Events between eviction drains in [`run_benchmark`], which keeps the
+/// bookkeeping out of the per-event path.
+const BENCHMARK_DRAIN_INTERVAL: u64 = 1024;
+
+/// Run the benchmark described by `config` against a cache from `make_cache`.
+///
+/// `make_cache` receives `config.capacity`. The workload's first
+/// `warmup_ops + operations` events are generated and classified up front,
+/// so neither is timed. The first `warmup_ops` events are then replayed with
+/// [`simulate`](crate::simulate) semantics but not recorded, and the next
+/// `operations` are timed through the same replay loop: the latency of each
+/// event (lookup plus insert on miss) is offered to a [`LatencySampler`],
+/// and evictions reported by the model are drained in batches and counted.
+/// Throughput is measured over the replayed events. `config.name` becomes
+/// the result's policy name.
+pub fn run_benchmark<C, F>(config: &BenchmarkConfig, make_cache: F) -> BenchmarkResult
+where
+    C: CacheModel,
+    F: FnOnce(usize) -> C,
+{
+    let mut cache = make_cache(config.capacity);
+    let mode = ReplayMode::default();
+    let mut generator = config.workload.generator();
+    let mut classifier = MissClassifier::default();
+    let events: Vec<(Event, Option<MissKind>)> = std::iter::from_fn(|| generator.next_event())
+        .take(config.warmup_ops + config.operations)
+        .map(|event| (event, Some(classifier.classify(&event, mode))))
+        .collect();
+    let (warmup, measured) = events.split_at(config.warmup_ops.min(events.len()));
+
+    let mut replay = Replay::new(mode).drain_every(BENCHMARK_DRAIN_INTERVAL);
+    replay.run(&mut cache, warmup.iter().copied(), &mut (), 0);
+
+    let mut latency = LatencyObserver {
+        sampler: LatencySampler::new(config.max_latency_samples, config.latency_sample_rate),
+        last: Instant::now(),
+    };
+    let start = Instant::now();
+    let stats = replay.run(
+        &mut cache,
+        measured.iter().copied(),
+        &mut latency,
+        warmup.len() as u64,
+    );
+    let duration = start.elapsed();
+
+    BenchmarkResult {
+        policy_name: config.name.clone(),
+        workload_name: config.workload.workload.name().to_string(),
+        capacity: config.capacity,
+        universe: config.workload.universe,
+        operations: measured.len() as u64,
+        hit_stats: stats,
+        throughput: ThroughputStats::from_operations(
+            measured.len() as u64,
+            stats.total_ops(),
+            stats.inserts + stats.updates,
+            duration,
+        ),
+        latency: latency.sampler.stats(),
+        eviction: stats.eviction_stats(),
+    }
+}
+
 // ============================================================================
 // Specialized Benchmarks
 // ============================================================================
 
-/// Measure scan resistance by interleaving point lookups with sequential scans.
+/// Configuration for [`measure_scan_resistance`].
+#[derive(Debug, Clone)]
+pub struct ScanResistanceConfig {
+    /// Cache capacity.
+    pub capacity: usize,
+    /// Point-lookup workload used before and after the scan.
+    pub workload: WorkloadSpec,
+    /// Unmeasured operations that fill the cache before the baseline phase.
+    pub warmup_ops: usize,
+    /// Measured operations before the scan.
+    pub baseline_ops: usize,
+    /// Scan length as a multiple of capacity (2.0 = scan twice the cache size).
+    pub scan_ratio: f64,
+    /// Measured operations after the scan.
+    pub recovery_ops: usize,
+}
+
+impl ScanResistanceConfig {
+    /// Number of keys touched by the scan phase.
+    pub fn scan_len(&self) -> u64 {
+        (self.capacity as f64 * self.scan_ratio.max(0.0)).round() as u64
+    }
+}
+
+impl Default for ScanResistanceConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+            workload: WorkloadSpec::default(),
+            warmup_ops: 20_000,
+            baseline_ops: 50_000,
+            scan_ratio: 2.0,
+            recovery_ops: 50_000,
+        }
+    }
+}
+
+/// Measure scan resistance of a cache from `make_cache`.
 ///
-/// Returns (baseline_hit_rate, scan_hit_rate, recovery_hit_rate).
-/// A scan-resistant policy should have recovery_hit_rate close to baseline_hit_rate.
+/// Runs three phases against one cache: a baseline on `config.workload`, a
+/// sequential scan of [`ScanResistanceConfig::scan_len`] keys that never
+/// appear in the workload, and a recovery phase that continues the same
+/// workload. A scan-resistant policy has a recovery hit rate close to the
+/// baseline.
+pub fn measure_scan_resistance<C, F>(
+    config: &ScanResistanceConfig,
+    make_cache: F,
+) -> ScanResistanceResult
+where
+    C: CacheModel,
+    F: FnOnce(usize) -> C,
+{
+    let mut cache = make_cache(config.capacity);
+    let mut generator = config.workload.generator();
+    let mut workload_phase = |cache: &mut C, ops: usize| {
+        let mut phase = std::iter::from_fn(|| generator.next_event()).take(ops);
+        crate::simulate(cache, &mut phase)
+    };
+
+    workload_phase(&mut cache, config.warmup_ops);
+    let baseline = workload_phase(&mut cache, config.baseline_ops);
+
+    let mut scan = (RESERVED_KEYS..RESERVED_KEYS + config.scan_len()).map(Event::get);
+    let scan = crate::simulate(&mut cache, &mut scan);
+
+    let recovery = workload_phase(&mut cache, config.recovery_ops);
+
+    let baseline_hit_rate = baseline.hit_rate();
+    let recovery_hit_rate = recovery.hit_rate();
+    ScanResistanceResult {
+        baseline_hit_rate,
+        scan_hit_rate: scan.hit_rate(),
+        recovery_hit_rate,
+        resistance_score: if baseline_hit_rate > 0.0 {
+            recovery_hit_rate / baseline_hit_rate
+        } else {
+            0.0
+        },
+    }
+}
+
 /// Results from scan resistance measurement.
 #[derive(Debug, Clone, Copy)]
 pub struct ScanResistanceResult {
@@ -287,6 +558,115 @@ impl ScanResistanceResult {
     }
 }
 
+/// First key of a range generated workloads do not reach: they draw keys
+/// below their universe, and op-mix inserts count up from just above it.
+/// Scans and disjoint shifts live here so they never collide with them.
+const RESERVED_KEYS: u64 = 1 << 63;
+
+/// Popularity shift applied by [`measure_adaptation`].
+#[derive(Debug, Clone)]
+pub enum AdaptationShift {
+    /// Continue the same workload on keys offset into a reserved range, so no
+    /// previously hot key stays hot.
+    DisjointKeys,
+    /// Switch to a different workload. Keys may overlap with the first one.
+    Workload(Box<WorkloadSpec>),
+}
+
+/// Configuration for [`measure_adaptation`].
+#[derive(Debug, Clone)]
+pub struct AdaptationConfig {
+    /// Cache capacity.
+    pub capacity: usize,
+    /// Workload the cache learns before the shift.
+    pub workload: WorkloadSpec,
+    /// What happens at the shift.
+    pub shift: AdaptationShift,
+    /// Operations replayed before the shift (not measured).
+    pub pre_shift_ops: usize,
+    /// Operations measured after the shift.
+    pub post_shift_ops: usize,
+    /// Operations per hit-rate window.
+    pub window: usize,
+}
+
+impl Default for AdaptationConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+            workload: WorkloadSpec::new(
+                16_384,
+                crate::workload::Workload::Zipfian { exponent: 1.0 },
+                42,
+            ),
+            shift: AdaptationShift::DisjointKeys,
+            pre_shift_ops: 100_000,
+            post_shift_ops: 100_000,
+            window: 1_000,
+        }
+    }
+}
+
+/// Measure how quickly a cache from `make_cache` re-learns after a shift.
+///
+/// The cache is trained on `config.workload` for `pre_shift_ops`, then the
+/// popularity shift is applied and hit rates are recorded per `window`
+/// operations. The stable hit rate is the mean of the last quarter of the
+/// windows; `ops_to_50_percent`/`ops_to_80_percent` count post-shift
+/// operations up to the end of the first window reaching that fraction of it
+/// (`post_shift_ops` if never reached).
+pub fn measure_adaptation<C, F>(config: &AdaptationConfig, make_cache: F) -> AdaptationResult
+where
+    C: CacheModel,
+    F: FnOnce(usize) -> C,
+{
+    let mut cache = make_cache(config.capacity);
+    let mut generator = config.workload.generator();
+    let mut warmup = std::iter::from_fn(|| generator.next_event()).take(config.pre_shift_ops);
+    crate::simulate(&mut cache, &mut warmup);
+
+    let (mut generator, offset) = match &config.shift {
+        AdaptationShift::DisjointKeys => (generator, RESERVED_KEYS),
+        AdaptationShift::Workload(spec) => (spec.generator(), 0),
+    };
+    let window = config.window.max(1);
+    let mut hit_rate_curve = Vec::with_capacity(config.post_shift_ops.div_ceil(window));
+    let mut remaining = config.post_shift_ops;
+    while remaining > 0 {
+        let ops = window.min(remaining);
+        let mut phase = std::iter::from_fn(|| generator.next_event())
+            .take(ops)
+            .map(|mut event| {
+                event.key = event.key.wrapping_add(offset);
+                event
+            });
+        hit_rate_curve.push(crate::simulate(&mut cache, &mut phase).hit_rate());
+        remaining -= ops;
+    }
+
+    let tail = &hit_rate_curve[hit_rate_curve.len() - hit_rate_curve.len().div_ceil(4)..];
+    let stable_hit_rate = if tail.is_empty() {
+        0.0
+    } else {
+        tail.iter().sum::<f64>() / tail.len() as f64
+    };
+    let ops_to = |fraction: f64| {
+        hit_rate_curve
+            .iter()
+            .position(|&rate| rate >= stable_hit_rate * fraction)
+            .map_or(config.post_shift_ops, |idx| {
+                ((idx + 1) * window).min(config.post_shift_ops)
+            })
+    };
+
+    AdaptationResult {
+        stable_hit_rate,
+        ops_to_50_percent: ops_to(0.5),
+        ops_to_80_percent: ops_to(0.8),
+        hit_rate_curve,
+    }
+}
+
 /// Results from adaptation speed measurement.
 #[derive(Debug, Clone)]
 pub struct AdaptationResult {
@@ -362,85 +742,66 @@ pub fn standard_workload_suite(universe: u64, seed: u64) -> Vec<(&'static str, W
     vec![
         (
             "uniform",
-            WorkloadSpec {
-                universe,
-                workload: Workload::Uniform,
-                seed,
-            },
+            WorkloadSpec::new(universe, Workload::Uniform, seed),
         ),
         (
             "zipfian_1.0",
-            WorkloadSpec {
-                universe,
-                workload: Workload::Zipfian { exponent: 1.0 },
-                seed,
-            },
+            WorkloadSpec::new(universe, Workload::Zipfian { exponent: 1.0 }, seed),
         ),
         (
             "zipfian_0.8",
-            WorkloadSpec {
-                universe,
-                workload: Workload::Zipfian { exponent: 0.8 },
-                seed,
-            },
+            WorkloadSpec::new(universe, Workload::Zipfian { exponent: 0.8 }, seed),
         ),
         (
             "hotset_90_10",
-            WorkloadSpec {
+            WorkloadSpec::new(
                 universe,
-                workload: Workload::HotSet {
+                Workload::HotSet {
                     hot_fraction: 0.1,
                     hot_prob: 0.9,
                 },
                 seed,
-            },
-        ),
-        (
-            "scan",
-            WorkloadSpec {
-                universe,
-                workload: Workload::Scan,
-                seed,
-            },
+            ),
         ),
+        ("scan", WorkloadSpec::new(universe, Workload::Scan, seed)),
         (
             "scan_resistance",
-            WorkloadSpec {
+            WorkloadSpec::new(
                 universe,
-                workload: Workload::ScanResistance {
+                Workload::ScanResistance {
                     scan_fraction: 0.2,
                     scan_length: 1000,
                     point_exponent: 1.0,
                 },
                 seed,
-            },
+            ),
         ),
         (
             "loop_small",
-            WorkloadSpec {
+            WorkloadSpec::new(
                 universe,
-                workload: Workload::Loop {
+                Workload::Loop {
                     working_set_size: 512,
                 },
                 seed,
-            },
+            ),
         ),
         (
             "shifting_hotspot",
-            WorkloadSpec {
+            WorkloadSpec::new(
                 universe,
-                workload: Workload::ShiftingHotspot {
+                Workload::ShiftingHotspot {
                     shift_interval: 10_000,
                     hot_fraction: 0.1,
                 },
                 seed,
-            },
+            ),
         ),
         (
             "flash_crowd",
-            WorkloadSpec {
+            WorkloadSpec::new(
                 universe,
-                workload: Workload::FlashCrowd {
+                Workload::FlashCrowd {
                     base_exponent: 1.0,
                     flash_prob: 0.001,
                     flash_duration: 1000,
@@ -448,7 +809,7 @@ pub fn standard_workload_suite(universe: u64, seed: u64) -> Vec<(&'static str, W
                     flash_intensity: 100.0,
                 },
                 seed,
-            },
+            ),
         ),
     ]
 }
@@ -465,7 +826,7 @@ where
     let cache_size = std::mem::size_of_val(cache);
     MemoryEstimate {
         total_bytes: cache_size,
-        bytes_per_entry: if entries > 0 { cache_size / entries } else { 0 },
+        bytes_per_entry: cache_size.checked_div(entries).unwrap_or(0),
         entry_count: entries,
     }
 }
@@ -488,3 +849,93 @@ impl MemoryEstimate {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_results::ResultRow;
+    use crate::policies::{Lru, S3Fifo};
+    use crate::workload::Workload;
+
+    #[test]
+    fn test_run_benchmark_populates_result() {
+        let config = BenchmarkConfig {
+            name: "LRU".to_string(),
+            capacity: 256,
+            warmup_ops: 1_000,
+            operations: 10_000,
+            ..BenchmarkConfig::default()
+        };
+        let result = run_benchmark(&config, Lru::new);
+
+        assert_eq!(result.operations, 10_000);
+        assert_eq!(result.hit_stats.total_ops(), 10_000);
+        // Read-through fills are part of their Get, not extra operations.
+        let throughput = result.throughput;
+        assert!((throughput.ops_per_sec - throughput.gets_per_sec).abs() < 1e-6);
+        assert!(result.hit_stats.evictions > 0);
+        assert!(result.hit_stats.hits > 0);
+        assert!(result.latency.sample_count > 0);
+        let stats = result.hit_stats;
+        assert_eq!(
+            stats.compulsory_misses + stats.capacity_misses + stats.delete_misses,
+            stats.misses
+        );
+
+        let row = ResultRow::from(&result);
+        assert_eq!(row.policy_id, "lru");
+        assert_eq!(row.case_id, "comprehensive");
+        assert_eq!(row.metrics.hit_stats.unwrap().hits, result.hit_stats.hits);
+    }
+
+    #[test]
+    fn test_scan_resistance_separates_policies() {
+        let config = ScanResistanceConfig {
+            capacity: 500,
+            workload: WorkloadSpec::new(5_000, Workload::Zipfian { exponent: 1.0 }, 7),
+            warmup_ops: 5_000,
+            baseline_ops: 10_000,
+            scan_ratio: 4.0,
+            recovery_ops: 2_000,
+        };
+        let lru = measure_scan_resistance(&config, Lru::new);
+        let s3_fifo = measure_scan_resistance(&config, S3Fifo::new);
+
+        assert_eq!(lru.scan_hit_rate, 0.0);
+        assert!(lru.baseline_hit_rate > 0.3);
+        assert!(s3_fifo.resistance_score > lru.resistance_score);
+
+        // Fresh inserts above the universe do not reach the scan keys.
+        let config = ScanResistanceConfig {
+            workload: WorkloadSpec::new(100, Workload::Uniform, 7)
+                .with_ops("read=1,insert=1".parse().unwrap()),
+            capacity: 10_000,
+            scan_ratio: 0.1,
+            ..config
+        };
+        assert_eq!(
+            measure_scan_resistance(&config, Lru::new).scan_hit_rate,
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_adaptation_converges_after_shift() {
+        let config = AdaptationConfig {
+            capacity: 200,
+            workload: WorkloadSpec::new(2_000, Workload::Zipfian { exponent: 1.0 }, 11),
+            shift: AdaptationShift::DisjointKeys,
+            pre_shift_ops: 10_000,
+            post_shift_ops: 20_000,
+            window: 500,
+        };
+        let result = measure_adaptation(&config, Lru::new);
+
+        assert_eq!(result.hit_rate_curve.len(), 40);
+        // Nothing hot before the shift is requested after it.
+        assert!(result.hit_rate_curve[0] < result.stable_hit_rate);
+        assert!(result.stable_hit_rate > 0.3);
+        assert!(result.ops_to_50_percent <= result.ops_to_80_percent);
+        assert!(result.ops_to_80_percent < config.post_shift_ops);
+    }
+}
@@ -0,0 +1,260 @@
+//! Weighted mixtures of workloads.
+//!
+//! ## Architecture
+//! A [`MixtureGenerator`] owns one [`WorkloadGenerator`] per
+//! [`MixtureComponent`] and, for every key, picks a component with
+//! probability proportional to its weight. Children only advance when they
+//! are picked, so a scan component keeps scanning where it left off.
+//! [`Workload::Mixture`] carries its components, so a mixture can be used
+//! (and serialized) wherever a single workload can.
+//!
+//! ## Key Spaces
+//! By default every component draws from the mixture's universe, so
+//! components overlap and compete for the same keys. A component can instead
+//! get its own universe and an offset added to every key it generates, which
+//! places it in a disjoint range, e.g. a scan over keys no other component
+//! touches.
+//!
+//! ## Example
+//! ```
+//! use tracekit::{BoundedGenerator, MixtureComponent, Workload, WorkloadGenerator};
+//!
+//! // 60% scrambled Zipf + 30% scans over a separate range + 10% latest.
+//! let components = vec![
+//!     MixtureComponent::new(0.6, Workload::ScrambledZipfian { exponent: 0.9 }),
+//!     MixtureComponent::new(0.3, Workload::Scan)
+//!         .with_offset(1 << 32)
+//!         .with_universe(50_000),
+//!     MixtureComponent::new(0.1, Workload::Latest { exponent: 1.0 }),
+//! ];
+//! let generator = WorkloadGenerator::mixture(10_000, &components, 42);
+//! let source = BoundedGenerator::new(generator, 100_000);
+//! # let _ = source;
+//! ```
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::event::Event;
+use crate::hash::child_seed;
+use crate::source::EventSource;
+use crate::workload::{Workload, WorkloadGenerator};
+
+/// One weighted child of a mixture.
+///
+/// Serializes as e.g. `{"weight": 0.3, "workload": {"kind": "scan"}, "offset": 4294967296}`;
+/// `offset` and `universe` may be omitted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MixtureComponent {
+    /// Relative share of the keys; weights need not sum to one.
+    pub weight: f64,
+    /// Workload generating this component's keys.
+    pub workload: Workload,
+    /// Added to every key the component generates.
+    #[serde(default)]
+    pub offset: u64,
+    /// Key universe of the component; `None` shares the mixture's universe.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub universe: Option<u64>,
+}
+
+impl MixtureComponent {
+    /// A component sharing the mixture's key space.
+    pub fn new(weight: f64, workload: Workload) -> Self {
+        Self {
+            weight,
+            workload,
+            offset: 0,
+            universe: None,
+        }
+    }
+
+    /// Shift the component's keys by `offset`.
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Give the component its own key universe.
+    pub fn with_universe(mut self, universe: u64) -> Self {
+        self.universe = Some(universe);
+        self
+    }
+}
+
+/// Default components of [`Workload::Mixture`]: 70% Zipfian, 20% scan,
+/// 10% uniform, all over the shared universe.
+pub const DEFAULT_MIXTURE: [MixtureComponent; 3] = [
+    MixtureComponent {
+        weight: 0.7,
+        workload: Workload::Zipfian { exponent: 1.0 },
+        offset: 0,
+        universe: None,
+    },
+    MixtureComponent {
+        weight: 0.2,
+        workload: Workload::Scan,
+        offset: 0,
+        universe: None,
+    },
+    MixtureComponent {
+        weight: 0.1,
+        workload: Workload::Uniform,
+        offset: 0,
+        universe: None,
+    },
+];
+
+/// Key generator drawing from weighted child workloads.
+#[derive(Debug, Clone)]
+pub struct MixtureGenerator {
+    rng: SmallRng,
+    /// Running weight totals, for picking a component.
+    cumulative: Vec<f64>,
+    children: Vec<(u64, WorkloadGenerator)>,
+}
+
+impl MixtureGenerator {
+    /// Create a mixture over `universe` keys.
+    ///
+    /// Each child is seeded from `seed` and its position, so a mixture is as
+    /// deterministic as its components.
+    ///
+    /// # Panics
+    /// Panics if `components` is empty, or if a weight is negative or not
+    /// finite, or if all weights are zero.
+    pub fn new(universe: u64, components: &[MixtureComponent], seed: u64) -> Self {
+        assert!(
+            !components.is_empty(),
+            "a mixture needs at least one component"
+        );
+        let mut total = 0.0;
+        let mut cumulative = Vec::with_capacity(components.len());
+        let mut children = Vec::with_capacity(components.len());
+        for (idx, component) in components.iter().enumerate() {
+            assert!(
+                component.weight.is_finite() && component.weight >= 0.0,
+                "invalid mixture weight {}",
+                component.weight
+            );
+            total += component.weight;
+            cumulative.push(total);
+            let generator = WorkloadGenerator::new(
+                component.universe.unwrap_or(universe),
+                component.workload.clone(),
+                child_seed(seed, idx as u64),
+            );
+            children.push((component.offset, generator));
+        }
+        assert!(total > 0.0, "mixture weights sum to zero");
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+            cumulative,
+            children,
+        }
+    }
+
+    /// Notify every component that a key was inserted (for Latest components).
+    pub fn record_insert(&mut self) {
+        for (_, child) in &mut self.children {
+            child.record_insert();
+        }
+    }
+
+    /// Next key, from a component picked by weight.
+    pub fn next_key(&mut self) -> u64 {
+        let total = self.cumulative[self.cumulative.len() - 1];
+        let pick = self.rng.random::<f64>() * total;
+        let idx = self
+            .cumulative
+            .iter()
+            .position(|&bound| pick < bound)
+            .unwrap_or(self.children.len() - 1);
+        let (offset, child) = &mut self.children[idx];
+        offset.wrapping_add(child.next_key())
+    }
+}
+
+/// `MixtureGenerator` implements `EventSource` as an infinite stream of Gets.
+impl EventSource for MixtureGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        Some(Event::get(self.next_key()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights_and_offsets() {
+        let components = [
+            MixtureComponent::new(3.0, Workload::Uniform),
+            MixtureComponent::new(1.0, Workload::Scan)
+                .with_offset(1_000_000)
+                .with_universe(10),
+        ];
+        let mut mixture = MixtureGenerator::new(100, &components, 7);
+        let keys: Vec<u64> = (0..10_000).map(|_| mixture.next_key()).collect();
+
+        let scanned: Vec<u64> = keys.iter().copied().filter(|&k| k >= 1_000_000).collect();
+        assert!(
+            keys.iter()
+                .all(|&k| k < 100 || (1_000_000..1_000_010).contains(&k))
+        );
+        // Roughly a quarter of the keys, and the scan resumes where it left off.
+        assert!((2_000..3_000).contains(&scanned.len()), "{}", scanned.len());
+        assert!(
+            scanned
+                .iter()
+                .enumerate()
+                .all(|(i, &k)| k == 1_000_000 + i as u64 % 10)
+        );
+    }
+
+    #[test]
+    fn test_zero_weight_component_is_never_picked() {
+        let components = [
+            MixtureComponent::new(0.0, Workload::Scan).with_offset(500),
+            MixtureComponent::new(1.0, Workload::Uniform),
+        ];
+        let mut mixture = MixtureGenerator::new(100, &components, 1);
+        assert!((0..1_000).all(|_| mixture.next_key() < 100));
+    }
+
+    #[test]
+    fn test_custom_mixture_round_trips_and_survives_set_workload() {
+        let json = r#"{"kind": "mixture", "components": [
+            {"weight": 1.0, "workload": {"kind": "uniform"}},
+            {"weight": 1.0, "workload": {"kind": "scan"}, "offset": 500, "universe": 10}
+        ]}"#;
+        let workload: Workload = serde_json::from_str(json).unwrap();
+        let Workload::Mixture { components } = &workload else {
+            panic!("{workload:?}");
+        };
+        assert_eq!(components[1].offset, 500);
+        assert_eq!(components[1].universe, Some(10));
+        let encoded = serde_json::to_string(&workload).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Workload>(&encoded).unwrap(),
+            workload
+        );
+        // Omitted components are the default mixture.
+        let default: Workload = serde_json::from_str(r#"{"kind": "mixture"}"#).unwrap();
+        assert_eq!(default, Workload::MIXTURE);
+
+        // Re-applying the same custom mixture keeps the generator's state.
+        let mut generator = WorkloadGenerator::new(100, workload.clone(), 3);
+        let mut reference = generator.clone();
+        generator.set_workload(workload.clone());
+        assert_eq!(generator.workload(), &workload);
+        assert!((0..1_000).all(|_| generator.next_key() == reference.next_key()));
+    }
+
+    #[test]
+    #[should_panic(expected = "sum to zero")]
+    fn test_all_zero_weights_panic() {
+        MixtureGenerator::new(100, &[MixtureComponent::new(0.0, Workload::Uniform)], 1);
+    }
+}
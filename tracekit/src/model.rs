@@ -11,7 +11,8 @@
 /// Minimal cache model for hit-rate simulation.
 ///
 /// Implementations should track their own capacity and eviction policy.
-/// The simulator only needs to know if a key is present and how to insert.
+/// The simulator only needs to look keys up and insert them; the remaining
+/// hooks are optional and have no-op defaults.
 pub trait CacheModel {
     /// Attempt a cache lookup. Returns `true` on hit, `false` on miss.
     fn get(&mut self, key: u64) -> bool;
@@ -19,8 +20,72 @@ pub trait CacheModel {
     /// Insert or update a key with unit weight.
     fn insert(&mut self, key: u64);
 
+    /// Insert or update a key with an explicit weight (e.g., object size in bytes).
+    ///
+    /// Size-aware models should override this to charge `weight` against their
+    /// capacity. The default implementation ignores the weight and falls back to
+    /// [`insert`](Self::insert), so unit-weight policies work unchanged.
+    fn insert_weighted(&mut self, key: u64, _weight: u32) {
+        self.insert(key);
+    }
+
+    /// Returns `true` if `key` is resident, without touching policy state.
+    ///
+    /// The simulator only asks when a replay counts updates or applies a
+    /// write policy (see [`ReplayMode`](crate::ReplayMode)); models used that
+    /// way must override the default, which reports every key as absent.
+    fn contains(&self, _key: u64) -> bool {
+        false
+    }
+
     /// Remove a key from the cache.
     ///
     /// Default implementation is a no-op for caches that don't support deletion.
     fn delete(&mut self, _key: u64) {}
+
+    /// Advance the model's logical clock to `now`.
+    ///
+    /// Called by time-aware simulation whenever event timestamps move the clock
+    /// forward. Time-aware policies can use this to age entries; the default
+    /// implementation ignores time.
+    fn advance_time(&mut self, _now: u64) {}
+
+    /// Move keys evicted since the last call into `out`.
+    ///
+    /// Models that track evictions should record every key displaced by
+    /// capacity pressure (not by [`delete`](Self::delete)) and hand them over
+    /// here. The simulator drains after each insert. The default reports
+    /// nothing, which leaves eviction statistics at zero.
+    fn drain_evictions(&mut self, _out: &mut Vec<u64>) {}
+}
+
+/// Forwarding implementation so boxed (including `dyn`) models can be simulated.
+impl<C: CacheModel + ?Sized> CacheModel for Box<C> {
+    fn get(&mut self, key: u64) -> bool {
+        (**self).get(key)
+    }
+
+    fn insert(&mut self, key: u64) {
+        (**self).insert(key)
+    }
+
+    fn insert_weighted(&mut self, key: u64, weight: u32) {
+        (**self).insert_weighted(key, weight)
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        (**self).contains(key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        (**self).delete(key)
+    }
+
+    fn advance_time(&mut self, now: u64) {
+        (**self).advance_time(now)
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        (**self).drain_evictions(out)
+    }
 }
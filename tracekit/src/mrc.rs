@@ -0,0 +1,729 @@
+//! Miss-ratio curves via stack (reuse) distance analysis.
+//!
+//! ## Architecture
+//! LRU has the inclusion property: a request hits in an LRU cache of
+//! capacity `c` iff its stack distance (number of distinct keys referenced
+//! since the previous reference to the same key, plus one) is at most `c`.
+//! A single pass that histograms stack distances therefore yields the LRU
+//! miss ratio for every capacity at once.
+//!
+//! ## Key Components
+//! - [`MattsonStack`]: exact stack distances (Fenwick tree, O(log n) per event)
+//! - [`Shards`]: spatially hashed sampling of [`MattsonStack`] (Waldspurger et
+//!   al., FAST 2015) for very large traces
+//! - [`CounterStacks`]: HyperLogLog counter stacks (Wires et al., OSDI 2014)
+//!   with bounded memory independent of the key universe
+//! - [`StackDistanceHistogram`]: analyzer output, converted into a
+//!   [`MissRatioCurve`] for the capacities of interest
+//!
+//! ## Semantics
+//! Gets are requests; Inserts move their key to the top of the stack without
+//! being counted; Deletes remove the key, so its next reference is a cold
+//! miss. [`CounterStacks`] cannot remove keys from its counters and treats
+//! every Get or Insert as a request.
+//!
+//! ## Example
+//! ```
+//! use tracekit::mrc::{analyze, MattsonStack};
+//! use tracekit::{BoundedGenerator, Workload, WorkloadSpec};
+//!
+//! let spec = WorkloadSpec::new(10_000, Workload::Zipfian { exponent: 1.0 }, 42);
+//! let mut source = BoundedGenerator::new(spec.generator(), 20_000);
+//! let histogram = analyze(MattsonStack::new(), &mut source);
+//! let curve = histogram.curve(&[100, 1_000, 10_000]);
+//! for point in &curve.points {
+//!     println!("{} -> {:.3}", point.capacity, point.miss_ratio);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::event::{Event, Op};
+use crate::hash::mix64;
+use crate::json_results;
+use crate::source::EventSource;
+
+// ============================================================================
+// Curves and histograms
+// ============================================================================
+
+/// Method used to compute a miss-ratio curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MrcMethod {
+    /// Exact Mattson stack distances.
+    Exact,
+    /// SHARDS fixed-rate sampling.
+    Shards { rate: f64 },
+    /// HyperLogLog counter stacks.
+    CounterStacks,
+}
+
+impl MrcMethod {
+    /// Stable identifier used in JSON artifacts.
+    pub fn id(&self) -> &'static str {
+        match self {
+            MrcMethod::Exact => "exact",
+            MrcMethod::Shards { .. } => "shards",
+            MrcMethod::CounterStacks => "counter_stacks",
+        }
+    }
+}
+
+/// Miss ratio at one capacity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MrcPoint {
+    pub capacity: usize,
+    pub miss_ratio: f64,
+}
+
+/// LRU miss ratio as a function of capacity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissRatioCurve {
+    pub method: MrcMethod,
+    pub points: Vec<MrcPoint>,
+}
+
+impl From<&MissRatioCurve> for json_results::MrcStats {
+    fn from(curve: &MissRatioCurve) -> Self {
+        json_results::MrcStats {
+            method: curve.method.id().to_string(),
+            points: curve
+                .points
+                .iter()
+                .map(|p| json_results::MrcPoint {
+                    capacity: p.capacity,
+                    miss_ratio: p.miss_ratio,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Histogram of stack distances produced by an analyzer.
+///
+/// Counts are `f64` because sampled analyzers scale them up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackDistanceHistogram {
+    method: MrcMethod,
+    /// `reuses[d - 1]` counts requests with stack distance `d`.
+    reuses: Vec<f64>,
+    cold_misses: f64,
+    requests: f64,
+}
+
+impl StackDistanceHistogram {
+    fn new(method: MrcMethod) -> Self {
+        Self {
+            method,
+            reuses: Vec::new(),
+            cold_misses: 0.0,
+            requests: 0.0,
+        }
+    }
+
+    fn record(&mut self, distance: usize, count: f64) {
+        let distance = distance.max(1);
+        if self.reuses.len() < distance {
+            self.reuses.resize(distance, 0.0);
+        }
+        self.reuses[distance - 1] += count;
+    }
+
+    /// Method that produced this histogram.
+    pub fn method(&self) -> MrcMethod {
+        self.method
+    }
+
+    /// Number of requests (Gets) analyzed, scaled for sampling.
+    pub fn requests(&self) -> f64 {
+        self.requests
+    }
+
+    /// Requests for keys never seen before (or seen only before a Delete).
+    pub fn cold_misses(&self) -> f64 {
+        self.cold_misses
+    }
+
+    /// Largest observed stack distance; capacities beyond it gain nothing.
+    pub fn max_distance(&self) -> usize {
+        self.reuses
+            .iter()
+            .rposition(|&c| c > 0.0)
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Number of requests with stack distance `distance`.
+    pub fn reuses_at(&self, distance: usize) -> f64 {
+        distance
+            .checked_sub(1)
+            .and_then(|i| self.reuses.get(i))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Number of requests that hit in an LRU cache of `capacity`.
+    pub fn hits(&self, capacity: usize) -> f64 {
+        self.reuses.iter().take(capacity).sum()
+    }
+
+    /// LRU miss ratio at a single capacity.
+    pub fn miss_ratio(&self, capacity: usize) -> f64 {
+        if self.requests <= 0.0 {
+            return 0.0;
+        }
+        ((self.requests - self.hits(capacity)) / self.requests).clamp(0.0, 1.0)
+    }
+
+    /// Miss-ratio curve evaluated at `capacities` (in the given order).
+    pub fn curve(&self, capacities: &[usize]) -> MissRatioCurve {
+        let mut cumulative = Vec::with_capacity(self.reuses.len() + 1);
+        cumulative.push(0.0);
+        let mut sum = 0.0;
+        for &count in &self.reuses {
+            sum += count;
+            cumulative.push(sum);
+        }
+
+        let points = capacities
+            .iter()
+            .map(|&capacity| {
+                let hits = cumulative[capacity.min(self.reuses.len())];
+                let miss_ratio = if self.requests > 0.0 {
+                    ((self.requests - hits) / self.requests).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                MrcPoint {
+                    capacity,
+                    miss_ratio,
+                }
+            })
+            .collect();
+
+        MissRatioCurve {
+            method: self.method,
+            points,
+        }
+    }
+}
+
+// ============================================================================
+// Analyzers
+// ============================================================================
+
+/// One-pass stack distance analyzer.
+pub trait StackDistanceAnalyzer {
+    /// Feed one event.
+    fn record(&mut self, event: &Event);
+
+    /// Histogram of everything recorded so far.
+    fn histogram(&self) -> StackDistanceHistogram;
+}
+
+/// Drain `source` through `analyzer` and return its histogram.
+pub fn analyze<A, S>(mut analyzer: A, source: &mut S) -> StackDistanceHistogram
+where
+    A: StackDistanceAnalyzer,
+    S: EventSource + ?Sized,
+{
+    while let Some(event) = source.next_event() {
+        analyzer.record(&event);
+    }
+    analyzer.histogram()
+}
+
+/// Growable Fenwick tree over reference timestamps (1-based).
+#[derive(Debug, Clone, Default)]
+struct Fenwick {
+    tree: Vec<i64>,
+}
+
+impl Fenwick {
+    fn new() -> Self {
+        Self { tree: vec![0] }
+    }
+
+    /// Number of slots.
+    fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Append a zero-valued slot.
+    fn push(&mut self) {
+        let i = self.tree.len();
+        let low = i & i.wrapping_neg();
+        let node = self.prefix(i - 1) - self.prefix(i - low);
+        self.tree.push(node);
+    }
+
+    fn add(&mut self, mut i: usize, delta: i64) {
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn prefix(&self, mut i: usize) -> i64 {
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+/// Exact LRU stack distances (Mattson et al., 1970).
+///
+/// Each resident key marks the timestamp of its latest reference in a Fenwick
+/// tree; the stack distance is the number of marks after the previous
+/// reference. Timestamps are compacted when they outgrow the key count, so
+/// memory is proportional to the number of distinct keys.
+#[derive(Debug, Clone)]
+pub struct MattsonStack {
+    marks: Fenwick,
+    last: HashMap<u64, usize>,
+    reuses: Vec<u64>,
+    cold_misses: u64,
+    requests: u64,
+}
+
+impl Default for MattsonStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MattsonStack {
+    /// Create an empty analyzer.
+    pub fn new() -> Self {
+        Self {
+            marks: Fenwick::new(),
+            last: HashMap::new(),
+            reuses: Vec::new(),
+            cold_misses: 0,
+            requests: 0,
+        }
+    }
+
+    /// Reference `key`, returning its stack distance (`None` if cold).
+    fn reference(&mut self, key: u64) -> Option<usize> {
+        if self.marks.len() >= 2 * self.last.len().max(1024) {
+            self.compact();
+        }
+        self.marks.push();
+        let now = self.marks.len();
+        let distance = self.last.insert(key, now).map(|prev| {
+            let newer = self.marks.prefix(now - 1) - self.marks.prefix(prev);
+            self.marks.add(prev, -1);
+            newer as usize + 1
+        });
+        self.marks.add(now, 1);
+        distance
+    }
+
+    fn forget(&mut self, key: u64) {
+        if let Some(prev) = self.last.remove(&key) {
+            self.marks.add(prev, -1);
+        }
+    }
+
+    /// Renumber live timestamps densely, preserving their order.
+    fn compact(&mut self) {
+        let mut live: Vec<(usize, u64)> = self.last.iter().map(|(&k, &t)| (t, k)).collect();
+        live.sort_unstable();
+        self.marks = Fenwick::new();
+        for (idx, &(_, key)) in live.iter().enumerate() {
+            self.marks.push();
+            self.marks.add(idx + 1, 1);
+            self.last.insert(key, idx + 1);
+        }
+    }
+}
+
+impl StackDistanceAnalyzer for MattsonStack {
+    fn record(&mut self, event: &Event) {
+        match event.op {
+            Op::Delete => self.forget(event.key),
+            Op::Insert => {
+                self.reference(event.key);
+            }
+            Op::Get => {
+                self.requests += 1;
+                match self.reference(event.key) {
+                    Some(d) => {
+                        if self.reuses.len() < d {
+                            self.reuses.resize(d, 0);
+                        }
+                        self.reuses[d - 1] += 1;
+                    }
+                    None => self.cold_misses += 1,
+                }
+            }
+        }
+    }
+
+    fn histogram(&self) -> StackDistanceHistogram {
+        StackDistanceHistogram {
+            method: MrcMethod::Exact,
+            reuses: self.reuses.iter().map(|&c| c as f64).collect(),
+            cold_misses: self.cold_misses as f64,
+            requests: self.requests as f64,
+        }
+    }
+}
+
+/// SHARDS: exact analysis of a spatially hashed sample of keys.
+///
+/// A key is sampled iff `hash(key) mod 2^24 < rate * 2^24`, so every reference
+/// to a sampled key is kept. Distances and counts are scaled by `1 / rate`,
+/// and the smallest-distance bucket absorbs the difference between expected
+/// and actual sampled requests (the SHARDS-adj correction).
+#[derive(Debug, Clone)]
+pub struct Shards {
+    rate: f64,
+    threshold: u64,
+    inner: MattsonStack,
+    requests: u64,
+}
+
+impl Shards {
+    const MODULUS: u64 = 1 << 24;
+
+    /// Sample roughly `rate` (0, 1] of the key space.
+    pub fn new(rate: f64) -> Self {
+        let rate = if rate > 0.0 { rate.min(1.0) } else { 1.0 };
+        let threshold = ((rate * Self::MODULUS as f64).round() as u64).max(1);
+        Self {
+            rate: threshold as f64 / Self::MODULUS as f64,
+            threshold,
+            inner: MattsonStack::new(),
+            requests: 0,
+        }
+    }
+
+    /// Effective sampling rate after rounding.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl StackDistanceAnalyzer for Shards {
+    fn record(&mut self, event: &Event) {
+        if event.op == Op::Get {
+            self.requests += 1;
+        }
+        if mix64(event.key) % Self::MODULUS < self.threshold {
+            self.inner.record(event);
+        }
+    }
+
+    fn histogram(&self) -> StackDistanceHistogram {
+        let scale = 1.0 / self.rate;
+        let mut histogram = StackDistanceHistogram::new(MrcMethod::Shards { rate: self.rate });
+        for (idx, &count) in self.inner.reuses.iter().enumerate() {
+            if count > 0 {
+                let distance = ((idx + 1) as f64 * scale).round() as usize;
+                histogram.record(distance, count as f64 * scale);
+            }
+        }
+        histogram.cold_misses = self.inner.cold_misses as f64 * scale;
+        histogram.requests = self.requests as f64;
+
+        let sampled = self.inner.requests as f64 * scale;
+        let adjustment = histogram.requests - sampled;
+        if adjustment != 0.0 && self.inner.requests > 0 {
+            let first = histogram.reuses.iter().position(|&c| c > 0.0).unwrap_or(0);
+            histogram.record(first + 1, adjustment);
+        }
+        histogram
+    }
+}
+
+/// HyperLogLog cardinality sketch.
+#[derive(Debug, Clone)]
+struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new(precision: u8) -> Self {
+        let precision = precision.clamp(4, 18);
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    fn insert(&mut self, hash: u64) {
+        let p = self.precision;
+        let idx = (hash >> (64 - p)) as usize;
+        let rank = ((hash << p) | (1 << (p - 1))).leading_zeros() as u8 + 1;
+        if self.registers[idx] < rank {
+            self.registers[idx] = rank;
+        }
+    }
+
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let mut sum = 0.0;
+        let mut zeros = 0usize;
+        for &r in &self.registers {
+            sum += 1.0 / (1u64 << r) as f64;
+            if r == 0 {
+                zeros += 1;
+            }
+        }
+        let raw = alpha * m * m / sum;
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+/// Configuration for [`CounterStacks`].
+#[derive(Debug, Clone, Copy)]
+pub struct CounterStackConfig {
+    /// References per window; a new counter starts at each window boundary.
+    pub window: usize,
+    /// Prune a counter once it is within this fraction of its older neighbour.
+    pub prune_delta: f64,
+    /// HyperLogLog precision (2^precision one-byte registers per counter).
+    pub precision: u8,
+}
+
+impl Default for CounterStackConfig {
+    fn default() -> Self {
+        Self {
+            window: 1_000,
+            prune_delta: 0.02,
+            precision: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Counter {
+    sketch: HyperLogLog,
+    last_estimate: f64,
+}
+
+/// Counter stacks: approximate stack distances from a stack of cardinality
+/// counters started at regular intervals.
+///
+/// At the end of each window, the growth of counter `i` is the number of
+/// keys in the window not referenced since counter `i` started. The
+/// difference between neighbouring counters' growth is the number of reuses
+/// whose previous reference fell between their start times; those reuses are
+/// attributed the midpoint of the two counters' cardinalities as their stack
+/// distance. Resolution is therefore bounded by the window length. Counters
+/// that converge with their neighbour are pruned, keeping memory logarithmic
+/// in trace length.
+#[derive(Debug, Clone)]
+pub struct CounterStacks {
+    config: CounterStackConfig,
+    counters: Vec<Counter>,
+    in_window: usize,
+    histogram: StackDistanceHistogram,
+}
+
+impl Default for CounterStacks {
+    fn default() -> Self {
+        Self::new(CounterStackConfig::default())
+    }
+}
+
+impl CounterStacks {
+    /// Create an analyzer with the given configuration.
+    pub fn new(config: CounterStackConfig) -> Self {
+        let config = CounterStackConfig {
+            window: config.window.max(1),
+            ..config
+        };
+        Self {
+            config,
+            counters: Vec::new(),
+            in_window: 0,
+            histogram: StackDistanceHistogram::new(MrcMethod::CounterStacks),
+        }
+    }
+
+    /// Number of live counters (memory is this times 2^precision bytes).
+    pub fn counters(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Close the current window into `histogram`.
+    fn flush(counters: &mut [Counter], in_window: usize, histogram: &mut StackDistanceHistogram) {
+        let estimates: Vec<f64> = counters.iter().map(|c| c.sketch.estimate()).collect();
+        let growth: Vec<f64> = counters
+            .iter()
+            .zip(&estimates)
+            .map(|(c, &e)| (e - c.last_estimate).max(0.0))
+            .collect();
+
+        histogram.requests += in_window as f64;
+        histogram.cold_misses += growth[0];
+        let mut attributed = growth[0];
+        for i in 0..counters.len() - 1 {
+            let reuses = (growth[i + 1] - growth[i]).max(0.0);
+            let distance = (estimates[i] + estimates[i + 1]) / 2.0;
+            histogram.record(distance.round() as usize, reuses);
+            attributed += reuses;
+        }
+        // Reuses within the window itself.
+        let newest = counters.len() - 1;
+        let remaining = (in_window as f64 - attributed).max(0.0);
+        histogram.record((estimates[newest] / 2.0).round() as usize, remaining);
+
+        for (counter, estimate) in counters.iter_mut().zip(estimates) {
+            counter.last_estimate = estimate;
+        }
+    }
+
+    fn prune(&mut self) {
+        let keep = 1.0 - self.config.prune_delta;
+        let mut i = 1;
+        while i < self.counters.len() {
+            if self.counters[i].last_estimate >= keep * self.counters[i - 1].last_estimate {
+                self.counters.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl StackDistanceAnalyzer for CounterStacks {
+    fn record(&mut self, event: &Event) {
+        if event.op == Op::Delete {
+            return;
+        }
+        if self.in_window == 0 {
+            self.counters.push(Counter {
+                sketch: HyperLogLog::new(self.config.precision),
+                last_estimate: 0.0,
+            });
+        }
+        let hash = mix64(event.key);
+        for counter in &mut self.counters {
+            counter.sketch.insert(hash);
+        }
+        self.in_window += 1;
+        if self.in_window == self.config.window {
+            Self::flush(&mut self.counters, self.in_window, &mut self.histogram);
+            self.in_window = 0;
+            self.prune();
+        }
+    }
+
+    fn histogram(&self) -> StackDistanceHistogram {
+        if self.in_window == 0 {
+            return self.histogram.clone();
+        }
+        let mut counters = self.counters.clone();
+        let mut histogram = self.histogram.clone();
+        Self::flush(&mut counters, self.in_window, &mut histogram);
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policies::Lru;
+    use crate::simulate;
+    use crate::workload::{BoundedGenerator, Workload, WorkloadSpec};
+
+    fn zipf_trace(universe: u64, ops: usize) -> Vec<Event> {
+        let spec = WorkloadSpec::new(universe, Workload::Zipfian { exponent: 0.9 }, 11);
+        let mut source = BoundedGenerator::new(spec.generator(), ops);
+        std::iter::from_fn(|| source.next_event()).collect()
+    }
+
+    #[test]
+    fn test_mattson_distances() {
+        let keys = [1, 2, 3, 1, 1, 3, 2];
+        let mut source = keys.iter().map(|&k| Event::get(k));
+        let histogram = analyze(MattsonStack::new(), &mut source);
+
+        assert_eq!(histogram.cold_misses(), 3.0);
+        assert_eq!(histogram.reuses_at(1), 1.0); // 1 after 1
+        assert_eq!(histogram.reuses_at(2), 1.0); // 3 after {1}
+        assert_eq!(histogram.reuses_at(3), 2.0); // 1 after {2,3}, 2 after {3,1}
+        assert_eq!(histogram.max_distance(), 3);
+    }
+
+    #[test]
+    fn test_mattson_matches_lru_simulation() {
+        let trace = zipf_trace(2_000, 20_000);
+        let histogram = analyze(MattsonStack::new(), &mut trace.iter().copied());
+        let capacities = [10, 100, 500, 1_000];
+        let curve = histogram.curve(&capacities);
+
+        for point in &curve.points {
+            let stats = simulate(&mut Lru::new(point.capacity), &mut trace.iter().copied());
+            assert!(
+                (point.miss_ratio - stats.miss_rate()).abs() < 1e-12,
+                "capacity {}: {} vs {}",
+                point.capacity,
+                point.miss_ratio,
+                stats.miss_rate()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mattson_delete_makes_next_reference_cold() {
+        let events = [Event::get(1), Event::delete(1), Event::get(1)];
+        let histogram = analyze(MattsonStack::new(), &mut events.iter().copied());
+        assert_eq!(histogram.cold_misses(), 2.0);
+        assert_eq!(histogram.miss_ratio(10), 1.0);
+    }
+
+    #[test]
+    fn test_shards_approximates_exact_curve() {
+        let trace = zipf_trace(20_000, 200_000);
+        let capacities = [500, 2_000, 8_000];
+        let exact = analyze(MattsonStack::new(), &mut trace.iter().copied()).curve(&capacities);
+        let approx = analyze(Shards::new(0.1), &mut trace.iter().copied()).curve(&capacities);
+
+        for (e, a) in exact.points.iter().zip(&approx.points) {
+            assert!(
+                (e.miss_ratio - a.miss_ratio).abs() < 0.05,
+                "capacity {}: exact {} vs shards {}",
+                e.capacity,
+                e.miss_ratio,
+                a.miss_ratio
+            );
+        }
+    }
+
+    #[test]
+    fn test_counter_stacks_approximates_exact_curve() {
+        let trace = zipf_trace(2_000, 100_000);
+        let capacities = [500, 1_000, 1_500];
+        let exact = analyze(MattsonStack::new(), &mut trace.iter().copied()).curve(&capacities);
+        let config = CounterStackConfig {
+            window: 100,
+            prune_delta: 0.02,
+            precision: 14,
+        };
+        let approx = analyze(CounterStacks::new(config), &mut trace.iter().copied());
+        let approx = approx.curve(&capacities);
+
+        for (e, a) in exact.points.iter().zip(&approx.points) {
+            assert!(
+                (e.miss_ratio - a.miss_ratio).abs() < 0.05,
+                "capacity {}: exact {} vs counter stacks {}",
+                e.capacity,
+                e.miss_ratio,
+                a.miss_ratio
+            );
+        }
+    }
+}
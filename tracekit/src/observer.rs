@@ -0,0 +1,501 @@
+//! Per-event simulation hooks.
+//!
+//! ## Architecture
+//! [`SimObserver`] receives every replayed event together with its
+//! [`Outcome`], plus every eviction it caused. Observers are driven by
+//! [`simulate_observed`](crate::simulate_observed), which owns the replay
+//! loop, so new metrics can be added without writing another simulation
+//! driver.
+//!
+//! ## Composition
+//! Observers compose: tuples of up to four observers, `Vec`s, `Option`s,
+//! `Box`es and `&mut` references are observers themselves, each forwarding to its parts
+//! in order. [`HitStats`] is an observer that counts outcomes.
+
+use std::collections::HashMap;
+
+use crate::event::{Event, Op};
+use crate::metrics::HitStats;
+use crate::simulator::{OnMiss, ReplayMode, Warmup, WritePolicy};
+
+/// Why a Get missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissKind {
+    /// First reference to the key.
+    Compulsory,
+    /// The key was referenced before and has not been invalidated since,
+    /// so the model chose not to keep it.
+    Capacity,
+    /// The key was invalidated (Delete or TTL expiry) since its last reference.
+    Delete,
+}
+
+/// Result of replaying one event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A Get found the key.
+    Hit,
+    /// A Get missed. `inserted` is `true` if the driver then filled the cache;
+    /// `kind` is set if the driver classifies misses.
+    Miss {
+        inserted: bool,
+        kind: Option<MissKind>,
+    },
+    /// An explicit Insert of a key that was not resident.
+    Insert,
+    /// An explicit Insert of a resident key.
+    Update,
+    /// An explicit Insert the [`WritePolicy`] kept out of the cache. Under
+    /// [`WritePolicy::Around`] a resident copy was removed.
+    Bypass,
+    /// A Delete.
+    Delete,
+}
+
+impl Outcome {
+    /// Returns `true` if the model received an insert for this event.
+    #[inline]
+    pub fn inserted(self) -> bool {
+        matches!(
+            self,
+            Outcome::Insert | Outcome::Update | Outcome::Miss { inserted: true, .. }
+        )
+    }
+}
+
+/// Hook receiving each simulated event and its outcome.
+///
+/// `index` is the zero-based position of the event within the current
+/// replay. Evictions are reported after the `on_event` call of the event
+/// whose insert caused them, with the same index. TTL expirations are
+/// reported before the `on_event` call of the event whose timestamp moved the
+/// clock past their deadline.
+pub trait SimObserver {
+    /// Called once per event, after the model has processed it.
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome);
+
+    /// Called for every key the model evicted while processing event `index`.
+    fn on_evict(&mut self, _index: u64, _key: u64) {}
+
+    /// Called for every resident key whose TTL elapsed before event `index`.
+    fn on_expire(&mut self, _index: u64, _key: u64) {}
+}
+
+/// Counts hits, classified misses, inserts, updates, bypasses, evictions and
+/// expirations.
+impl SimObserver for HitStats {
+    #[inline]
+    fn on_event(&mut self, _index: u64, _event: &Event, outcome: Outcome) {
+        match outcome {
+            Outcome::Hit => self.hits += 1,
+            Outcome::Miss { inserted, kind } => {
+                self.misses += 1;
+                self.inserts += inserted as u64;
+                if let Some(kind) = kind {
+                    self.record_miss(kind);
+                }
+            }
+            Outcome::Insert => self.inserts += 1,
+            Outcome::Update => self.updates += 1,
+            Outcome::Bypass => self.bypasses += 1,
+            Outcome::Delete => {}
+        }
+    }
+
+    #[inline]
+    fn on_evict(&mut self, _index: u64, _key: u64) {
+        self.evictions += 1;
+    }
+
+    #[inline]
+    fn on_expire(&mut self, _index: u64, _key: u64) {
+        self.expirations += 1;
+    }
+}
+
+impl<O: SimObserver + ?Sized> SimObserver for &mut O {
+    #[inline]
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        (**self).on_event(index, event, outcome)
+    }
+
+    #[inline]
+    fn on_evict(&mut self, index: u64, key: u64) {
+        (**self).on_evict(index, key)
+    }
+
+    #[inline]
+    fn on_expire(&mut self, index: u64, key: u64) {
+        (**self).on_expire(index, key)
+    }
+}
+
+impl<O: SimObserver + ?Sized> SimObserver for Box<O> {
+    #[inline]
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        (**self).on_event(index, event, outcome)
+    }
+
+    #[inline]
+    fn on_evict(&mut self, index: u64, key: u64) {
+        (**self).on_evict(index, key)
+    }
+
+    #[inline]
+    fn on_expire(&mut self, index: u64, key: u64) {
+        (**self).on_expire(index, key)
+    }
+}
+
+impl<O: SimObserver> SimObserver for Vec<O> {
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        for observer in self {
+            observer.on_event(index, event, outcome);
+        }
+    }
+
+    fn on_evict(&mut self, index: u64, key: u64) {
+        for observer in self {
+            observer.on_evict(index, key);
+        }
+    }
+
+    fn on_expire(&mut self, index: u64, key: u64) {
+        for observer in self {
+            observer.on_expire(index, key);
+        }
+    }
+}
+
+/// Forwards to the observer if there is one.
+impl<O: SimObserver> SimObserver for Option<O> {
+    #[inline]
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        if let Some(observer) = self {
+            observer.on_event(index, event, outcome);
+        }
+    }
+
+    #[inline]
+    fn on_evict(&mut self, index: u64, key: u64) {
+        if let Some(observer) = self {
+            observer.on_evict(index, key);
+        }
+    }
+
+    #[inline]
+    fn on_expire(&mut self, index: u64, key: u64) {
+        if let Some(observer) = self {
+            observer.on_expire(index, key);
+        }
+    }
+}
+
+/// Does nothing; the observer for drivers that only need totals.
+impl SimObserver for () {
+    #[inline]
+    fn on_event(&mut self, _index: u64, _event: &Event, _outcome: Outcome) {}
+}
+
+macro_rules! impl_tuple_observer {
+    ($($name:ident),+) => {
+        impl<$($name: SimObserver),+> SimObserver for ($($name,)+) {
+            #[inline]
+            #[allow(non_snake_case)]
+            fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+                let ($($name,)+) = self;
+                $($name.on_event(index, event, outcome);)+
+            }
+
+            #[inline]
+            #[allow(non_snake_case)]
+            fn on_evict(&mut self, index: u64, key: u64) {
+                let ($($name,)+) = self;
+                $($name.on_evict(index, key);)+
+            }
+
+            #[inline]
+            #[allow(non_snake_case)]
+            fn on_expire(&mut self, index: u64, key: u64) {
+                let ($($name,)+) = self;
+                $($name.on_expire(index, key);)+
+            }
+        }
+    };
+}
+
+impl_tuple_observer!(A);
+impl_tuple_observer!(A, B);
+impl_tuple_observer!(A, B, C);
+impl_tuple_observer!(A, B, C, D);
+
+/// Per-key reference history used to classify misses.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MissClassifier {
+    /// Every referenced key, mapped to whether it was invalidated since.
+    keys: HashMap<u64, bool>,
+}
+
+impl MissClassifier {
+    /// Classify a miss on `key`, before [`reference`](Self::reference) is called.
+    #[inline]
+    pub(crate) fn miss_kind(&self, key: u64) -> MissKind {
+        match self.keys.get(&key) {
+            None => MissKind::Compulsory,
+            Some(false) => MissKind::Capacity,
+            Some(true) => MissKind::Delete,
+        }
+    }
+
+    /// Update the history with `event`, returning the kind a miss on it has.
+    ///
+    /// Only meaningful for Gets; other events return `Capacity`. An event
+    /// only counts as a reference if `mode` stores its key: Gets under
+    /// [`OnMiss::Insert`], writes under [`WritePolicy::AllocateOnMiss`]. Writes
+    /// invalidate the key under [`WritePolicy::Around`].
+    #[inline]
+    pub(crate) fn classify(&mut self, event: &Event, mode: ReplayMode) -> MissKind {
+        match (event.op, mode.writes) {
+            (Op::Get, _) => {
+                let kind = self.miss_kind(event.key);
+                if mode.on_miss == OnMiss::Insert {
+                    self.reference(event.key);
+                }
+                return kind;
+            }
+            (Op::Insert, WritePolicy::AllocateOnMiss) => self.reference(event.key),
+            (Op::Insert, WritePolicy::Through) => {}
+            (Op::Insert, WritePolicy::Around) | (Op::Delete, _) => self.invalidate(event.key),
+        }
+        MissKind::Capacity
+    }
+
+    /// Record a Get or Insert of `key`.
+    #[inline]
+    pub(crate) fn reference(&mut self, key: u64) {
+        self.keys.insert(key, false);
+    }
+
+    /// Record that `key` was deleted or expired.
+    #[inline]
+    pub(crate) fn invalidate(&mut self, key: u64) {
+        if let Some(invalidated) = self.keys.get_mut(&key) {
+            *invalidated = true;
+        }
+    }
+}
+
+/// Forwards to `inner` only once a [`Warmup`] has passed.
+///
+/// Events, evictions and expirations during warmup are swallowed; so is the
+/// eviction that ends a [`Warmup::UntilFull`] warmup.
+#[derive(Debug, Clone)]
+pub struct WarmupFilter<O> {
+    /// Warmup events left to skip; `None` waits for the first eviction.
+    remaining: Option<u64>,
+    warm: bool,
+    skipped: u64,
+    inner: O,
+}
+
+impl<O> WarmupFilter<O> {
+    /// Wrap `inner`. `trace_len` resolves [`Warmup::Fraction`]; without it a
+    /// fractional warmup is empty.
+    pub fn new(warmup: Warmup, trace_len: Option<u64>, inner: O) -> Self {
+        let remaining = match (warmup, trace_len) {
+            (Warmup::Fraction(_), None) => Some(0),
+            (warmup, len) => warmup.events(len.unwrap_or(0)),
+        };
+        Self {
+            remaining,
+            warm: remaining == Some(0),
+            skipped: 0,
+            inner,
+        }
+    }
+
+    /// Returns `true` once warmup is over.
+    pub fn is_warm(&self) -> bool {
+        self.warm
+    }
+
+    /// Number of events excluded as warmup so far.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// The wrapped observer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Unwrap the inner observer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: SimObserver> SimObserver for WarmupFilter<O> {
+    #[inline]
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        if !self.warm {
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= 1;
+                self.warm = *remaining == 0;
+            }
+            self.skipped += 1;
+            return;
+        }
+        self.inner.on_event(index, event, outcome);
+    }
+
+    #[inline]
+    fn on_evict(&mut self, index: u64, key: u64) {
+        if self.warm {
+            self.inner.on_evict(index, key);
+        } else if self.remaining.is_none() {
+            self.warm = true;
+        }
+    }
+
+    #[inline]
+    fn on_expire(&mut self, index: u64, key: u64) {
+        if self.warm {
+            self.inner.on_expire(index, key);
+        }
+    }
+}
+
+/// Adapts an eviction callback `FnMut(key, index)` into an observer.
+pub(crate) struct OnEvict<F>(pub(crate) F);
+
+impl<F: FnMut(u64, u64)> SimObserver for OnEvict<F> {
+    #[inline]
+    fn on_event(&mut self, _index: u64, _event: &Event, _outcome: Outcome) {}
+
+    #[inline]
+    fn on_evict(&mut self, index: u64, key: u64) {
+        (self.0)(key, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policies::Lru;
+    use crate::simulator::{OnMiss, ReplayMode, simulate_observed};
+
+    /// Records the outcome sequence.
+    #[derive(Default)]
+    struct Log(Vec<Outcome>);
+
+    impl SimObserver for Log {
+        fn on_event(&mut self, _index: u64, _event: &Event, outcome: Outcome) {
+            self.0.push(outcome);
+        }
+    }
+
+    #[test]
+    fn test_observers_compose() {
+        let events = vec![
+            Event::get(1),
+            Event::get(1),
+            Event::insert(2),
+            Event::delete(1),
+            Event::get(3),
+        ];
+        let mut cache = Lru::new(1);
+        let mut observers = (Log::default(), vec![HitStats::default(); 2]);
+        let totals = simulate_observed(
+            &mut cache,
+            &mut events.into_iter(),
+            OnMiss::Insert,
+            &mut observers,
+        );
+
+        let (log, stats) = observers;
+        assert_eq!(
+            log.0,
+            vec![
+                Outcome::Miss {
+                    inserted: true,
+                    kind: None
+                },
+                Outcome::Hit,
+                Outcome::Insert,
+                Outcome::Delete,
+                Outcome::Miss {
+                    inserted: true,
+                    kind: None
+                },
+            ]
+        );
+        assert_eq!(stats[0], totals);
+        assert_eq!(stats[1], totals);
+        assert_eq!(totals.evictions, 2);
+    }
+
+    #[test]
+    fn test_skip_on_miss_does_not_insert() {
+        let events = vec![Event::get(1), Event::get(1)];
+        let mut cache = Lru::new(4);
+        let mut log = Log::default();
+        let stats = simulate_observed(&mut cache, &mut events.into_iter(), OnMiss::Skip, &mut log);
+
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.inserts, 0);
+        assert!(log.0.iter().all(|o| !o.inserted()));
+    }
+
+    #[test]
+    fn test_misses_are_classified() {
+        let events = vec![
+            Event::get(1),    // compulsory
+            Event::get(2),    // compulsory, evicts 1
+            Event::get(1),    // capacity, evicts 2
+            Event::delete(1), // invalidates 1
+            Event::get(1),    // delete
+            Event::insert(3), // evicts 1
+            Event::get(3),    // hit
+        ];
+        let mode = ReplayMode {
+            classify_misses: true,
+            ..ReplayMode::default()
+        };
+        let stats = simulate_observed(
+            &mut Lru::new(1),
+            &mut events.clone().into_iter(),
+            mode,
+            &mut (),
+        );
+
+        assert_eq!(stats.misses, 4);
+        assert_eq!(stats.compulsory_misses, 2);
+        assert_eq!(stats.capacity_misses, 1);
+        assert_eq!(stats.delete_misses, 1);
+
+        // Classification is opt-in.
+        let stats = simulate_observed(
+            &mut Lru::new(1),
+            &mut events.into_iter(),
+            OnMiss::Insert,
+            &mut (),
+        );
+        assert_eq!(stats.misses, 4);
+        assert_eq!(stats.compulsory_misses, 0);
+
+        // Without auto-insert, a Get miss never brings the key in.
+        let events = vec![
+            Event::get(1),
+            Event::get(1),
+            Event::insert(1),
+            Event::get(2),
+        ];
+        let mode = ReplayMode {
+            on_miss: OnMiss::Skip,
+            ..mode
+        };
+        let stats = simulate_observed(&mut Lru::new(1), &mut events.into_iter(), mode, &mut ());
+        assert_eq!((stats.misses, stats.compulsory_misses), (3, 3));
+    }
+}
@@ -0,0 +1,314 @@
+//! Operation mix of generated traces.
+//!
+//! ## Architecture
+//! An [`OpMix`] gives the relative weight of each kind of operation a
+//! [`WorkloadGenerator`](crate::WorkloadGenerator) emits. Reads, updates,
+//! deletes and read-modify-writes target keys from the workload's key
+//! distribution; inserts write fresh keys above the universe (see below). The default mix is
+//! read-only, which reproduces the plain Get streams of earlier versions.
+//!
+//! ## Operations
+//! - read: `Op::Get` of a key from the distribution
+//! - update: `Op::Insert` of a key from the distribution
+//! - insert: `Op::Insert` of the next fresh key, `universe + 1`, `universe + 2`,
+//!   ...; a Latest distribution favors the newest of them, so new keys are hot
+//! - delete: `Op::Delete` of a key from the distribution
+//! - read-modify-write: a Get followed by an Insert of the same key (two events)
+//!
+//! `Event` has no separate update op, so updates, fresh inserts and the write
+//! half of a read-modify-write all reach a trace as `"op":"insert"`. Replay
+//! does not need the distinction (a write to a resident key counts as an
+//! update either way); to recover it from a trace, compare keys against the
+//! universe, since only fresh inserts lie above it.
+//!
+//! ## YCSB Presets
+//! [`Ycsb`] provides the six core YCSB workloads as an op mix plus a key
+//! distribution ([`Ycsb::workload`]). Workload E issues short range scans,
+//! which are approximated by [`Workload::Correlated`] runs of reads.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::workload::Workload;
+
+/// Relative weights of the operations in a generated trace.
+///
+/// Weights need not sum to one; a mix with all weights zero is read-only.
+/// Omitted fields deserialize as zero.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OpMix {
+    /// Gets of existing keys.
+    #[serde(default)]
+    pub read: f64,
+    /// Inserts of keys from the distribution.
+    #[serde(default)]
+    pub update: f64,
+    /// Inserts of keys above the universe, never generated before.
+    #[serde(default)]
+    pub insert: f64,
+    /// Deletes of keys from the distribution.
+    #[serde(default)]
+    pub delete: f64,
+    /// Get-then-Insert pairs on one key.
+    #[serde(default)]
+    pub read_modify_write: f64,
+}
+
+/// Operation drawn from an [`OpMix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MixOp {
+    Read,
+    Update,
+    Insert,
+    Delete,
+    ReadModifyWrite,
+}
+
+impl OpMix {
+    /// Reads only.
+    pub const READ_ONLY: OpMix = OpMix {
+        read: 1.0,
+        update: 0.0,
+        insert: 0.0,
+        delete: 0.0,
+        read_modify_write: 0.0,
+    };
+
+    /// Returns `true` if the mix only ever reads.
+    pub fn is_read_only(&self) -> bool {
+        self.update <= 0.0
+            && self.insert <= 0.0
+            && self.delete <= 0.0
+            && self.read_modify_write <= 0.0
+    }
+
+    /// Pick an operation given `r` uniform in `[0, 1)`.
+    pub(crate) fn pick(&self, r: f64) -> MixOp {
+        let weights = [
+            (self.read.max(0.0), MixOp::Read),
+            (self.update.max(0.0), MixOp::Update),
+            (self.insert.max(0.0), MixOp::Insert),
+            (self.delete.max(0.0), MixOp::Delete),
+            (self.read_modify_write.max(0.0), MixOp::ReadModifyWrite),
+        ];
+        let total: f64 = weights.iter().map(|(w, _)| w).sum();
+        let mut pick = r * total;
+        for (weight, op) in weights {
+            if pick < weight {
+                return op;
+            }
+            pick -= weight;
+        }
+        MixOp::Read
+    }
+}
+
+impl Default for OpMix {
+    fn default() -> Self {
+        Self::READ_ONLY
+    }
+}
+
+/// Parses `read=0.5,update=0.5`-style weights (omitted operations get zero,
+/// `rmw` is accepted for `read_modify_write`) or a YCSB preset name such as
+/// `ycsb-a`.
+impl FromStr for OpMix {
+    type Err = ParseOpMixError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        if let Ok(preset) = spec.parse::<Ycsb>() {
+            return Ok(preset.op_mix());
+        }
+
+        let mut mix = OpMix {
+            read: 0.0,
+            ..OpMix::READ_ONLY
+        };
+        for pair in spec.split(',') {
+            let err = || ParseOpMixError(spec.to_string());
+            let (name, value) = pair.split_once('=').ok_or_else(err)?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|v: &f64| v.is_finite() && *v >= 0.0)
+                .ok_or_else(err)?;
+            let slot = match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+                "read" => &mut mix.read,
+                "update" => &mut mix.update,
+                "insert" => &mut mix.insert,
+                "delete" => &mut mix.delete,
+                "read_modify_write" | "rmw" => &mut mix.read_modify_write,
+                _ => return Err(err()),
+            };
+            *slot = value;
+        }
+        Ok(mix)
+    }
+}
+
+/// Error from parsing an [`OpMix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOpMixError(String);
+
+impl fmt::Display for ParseOpMixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid operation mix '{}' (expected e.g. 'read=0.9,update=0.1' or 'ycsb-a')",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseOpMixError {}
+
+/// Core YCSB workloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ycsb {
+    /// Update heavy: 50% reads, 50% updates.
+    A,
+    /// Read mostly: 95% reads, 5% updates.
+    B,
+    /// Read only.
+    C,
+    /// Read latest: 95% reads, 5% inserts, reads favor recent inserts.
+    D,
+    /// Short ranges: 95% scans, 5% inserts.
+    E,
+    /// Read-modify-write: 50% reads, 50% read-modify-writes.
+    F,
+}
+
+impl Ycsb {
+    /// All presets, in order.
+    pub const ALL: [Ycsb; 6] = [Ycsb::A, Ycsb::B, Ycsb::C, Ycsb::D, Ycsb::E, Ycsb::F];
+
+    /// Operation mix of the preset.
+    pub fn op_mix(self) -> OpMix {
+        let (read, update, insert, read_modify_write) = match self {
+            Ycsb::A => (0.5, 0.5, 0.0, 0.0),
+            Ycsb::B => (0.95, 0.05, 0.0, 0.0),
+            Ycsb::C => (1.0, 0.0, 0.0, 0.0),
+            Ycsb::D | Ycsb::E => (0.95, 0.0, 0.05, 0.0),
+            Ycsb::F => (0.5, 0.0, 0.0, 0.5),
+        };
+        OpMix {
+            read,
+            update,
+            insert,
+            delete: 0.0,
+            read_modify_write,
+        }
+    }
+
+    /// Request distribution of the preset: scrambled Zipfian (0.99), latest
+    /// for D, and runs of 100 sequential keys from random start keys for E.
+    pub fn workload(self) -> Workload {
+        match self {
+            Ycsb::A | Ycsb::B | Ycsb::C | Ycsb::F => Workload::ScrambledZipfian { exponent: 0.99 },
+            Ycsb::D => Workload::Latest { exponent: 0.99 },
+            Ycsb::E => Workload::Correlated {
+                stride: 1,
+                burst_len: 100,
+                burst_prob: 1.0,
+            },
+        }
+    }
+}
+
+/// Parses `ycsb-a` through `ycsb-f` (or just `a` through `f`), case-insensitively.
+impl FromStr for Ycsb {
+    type Err = ParseOpMixError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let lower = name.trim().to_ascii_lowercase();
+        let letter = lower
+            .strip_prefix("ycsb-")
+            .or_else(|| lower.strip_prefix("ycsb_"))
+            .unwrap_or(&lower);
+        match letter {
+            "a" => Ok(Ycsb::A),
+            "b" => Ok(Ycsb::B),
+            "c" => Ok(Ycsb::C),
+            "d" => Ok(Ycsb::D),
+            "e" => Ok(Ycsb::E),
+            "f" => Ok(Ycsb::F),
+            _ => Err(ParseOpMixError(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_weights_and_presets() {
+        let mix: OpMix = "read=0.8, update=0.1,rmw=0.1".parse().unwrap();
+        assert_eq!(mix.read, 0.8);
+        assert_eq!(mix.update, 0.1);
+        assert_eq!(mix.read_modify_write, 0.1);
+        assert_eq!(mix.delete, 0.0);
+
+        assert_eq!("YCSB-F".parse::<OpMix>().unwrap(), Ycsb::F.op_mix());
+        assert!("read=x".parse::<OpMix>().is_err());
+        assert!("reads=1".parse::<OpMix>().is_err());
+        assert!("read=-1".parse::<OpMix>().is_err());
+    }
+
+    #[test]
+    fn test_pick_follows_weights() {
+        let mix = Ycsb::A.op_mix();
+        assert_eq!(mix.pick(0.25), MixOp::Read);
+        assert_eq!(mix.pick(0.75), MixOp::Update);
+        assert!(Ycsb::C.op_mix().is_read_only());
+        assert!(!Ycsb::D.op_mix().is_read_only());
+    }
+
+    #[test]
+    fn test_generator_emits_mix() {
+        use crate::event::{Event, Op};
+        use crate::source::EventSource;
+        use crate::workload::WorkloadSpec;
+
+        let mut source = WorkloadSpec::ycsb(Ycsb::F, 1_000, 5).generator();
+        let events: Vec<Event> = (0..10_000).filter_map(|_| source.next_event()).collect();
+        // Every write of a read-modify-write follows a read of the same key.
+        for (idx, event) in events.iter().enumerate() {
+            if event.op == Op::Insert {
+                assert_eq!(events[idx - 1], Event::get(event.key));
+            }
+        }
+        let writes = events.iter().filter(|e| e.op == Op::Insert).count();
+        assert!((2_500..4_000).contains(&writes), "{writes}");
+
+        // Inserts write fresh keys above the universe, in order.
+        let mut source = WorkloadSpec::new(100, Workload::Uniform, 1)
+            .with_ops("insert=1".parse().unwrap())
+            .generator();
+        let keys: Vec<u64> = (0..3)
+            .filter_map(|_| source.next_event())
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys, vec![101, 102, 103]);
+
+        // Latest reads favor the fresh keys, but only ones already inserted.
+        let mut source = WorkloadSpec::ycsb(Ycsb::D, 1_000, 5).generator();
+        let mut newest = 1_000;
+        let mut fresh_reads = 0;
+        for event in (0..10_000).filter_map(|_| source.next_event()) {
+            if event.op == Op::Insert {
+                assert_eq!(event.key, newest + 1);
+                newest = event.key;
+            } else if event.key > 1_000 {
+                assert!(event.key <= newest);
+                fresh_reads += 1;
+            }
+        }
+        assert!(fresh_reads > 1_000, "{fresh_reads}");
+    }
+}
@@ -0,0 +1,346 @@
+//! Offline optimal replacement (Bélády's MIN).
+//!
+//! ## Architecture
+//! Optimal replacement needs to see the future, so it cannot be expressed as a
+//! [`CacheModel`](crate::CacheModel). [`OfflineTrace`] instead buffers a whole
+//! [`EventSource`] once, pre-computes the next-use distance of every access,
+//! and can then replay the optimal policy at any capacity. The resulting
+//! [`HitStats`] is an upper bound on the hit rate of any online policy.
+//!
+//! ## Key Components
+//! - [`OfflineTrace::belady`]: MIN with unit weights (exactly optimal)
+//! - [`OfflineTrace::belady_warm`]: the same, excluding a warmup prefix
+//! - [`OfflineTrace::belady_weighted`]: size-aware variant with byte capacity
+//!
+//! ## Semantics
+//! Replay mirrors [`simulate`](crate::simulate): Gets hit or miss, Inserts
+//! and Get misses are candidates for admission, Deletes invalidate. MIN is
+//! allowed to bypass an object whose next use is farther away than every
+//! resident object's, which never lowers the hit count. Inserts of resident
+//! keys count as updates, as with
+//! [`ReplayMode::count_updates`](crate::ReplayMode::count_updates).
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::event::Op;
+use crate::metrics::HitStats;
+use crate::observer::MissClassifier;
+use crate::simulator::Warmup;
+use crate::source::EventSource;
+
+/// Next-use marker for keys that are never referenced again.
+const NEVER: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy)]
+struct Access {
+    key: u64,
+    op: Op,
+    weight: u32,
+    next_use: usize,
+}
+
+/// A fully buffered trace annotated with next-use positions.
+#[derive(Debug, Clone, Default)]
+pub struct OfflineTrace {
+    accesses: Vec<Access>,
+    unique_keys: usize,
+}
+
+impl OfflineTrace {
+    /// Buffer every event from `source` and compute next-use distances.
+    pub fn from_source<S: EventSource + ?Sized>(source: &mut S) -> Self {
+        let mut accesses = Vec::with_capacity(source.size_hint().unwrap_or(0));
+        while let Some(event) = source.next_event() {
+            accesses.push(Access {
+                key: event.key,
+                op: event.op,
+                weight: event.weight.unwrap_or(1),
+                next_use: NEVER,
+            });
+        }
+
+        // Backward scan: a Delete ends the reuse chain of everything before it.
+        let mut next_seen: HashMap<u64, usize> = HashMap::new();
+        let mut unique = HashSet::new();
+        for (idx, access) in accesses.iter_mut().enumerate().rev() {
+            unique.insert(access.key);
+            match access.op {
+                Op::Delete => {
+                    next_seen.remove(&access.key);
+                }
+                Op::Get | Op::Insert => {
+                    access.next_use = next_seen.insert(access.key, idx).unwrap_or(NEVER);
+                }
+            }
+        }
+
+        Self {
+            unique_keys: unique.len(),
+            accesses,
+        }
+    }
+
+    /// Number of buffered events.
+    pub fn len(&self) -> usize {
+        self.accesses.len()
+    }
+
+    /// Returns `true` if the trace has no events.
+    pub fn is_empty(&self) -> bool {
+        self.accesses.is_empty()
+    }
+
+    /// Number of distinct keys in the trace.
+    pub fn unique_keys(&self) -> usize {
+        self.unique_keys
+    }
+
+    /// Replay Bélády's MIN with room for `capacity` entries.
+    pub fn belady(&self, capacity: usize) -> HitStats {
+        self.belady_warm(capacity, Warmup::Events(0))
+    }
+
+    /// Replay Bélády's MIN, excluding `warmup` from the statistics.
+    ///
+    /// Warmup events still shape the cache contents, exactly as in
+    /// [`simulate_warm`](crate::simulate_warm). Since MIN may bypass instead
+    /// of evicting, [`Warmup::UntilFull`] ends with the first admission
+    /// decision taken at capacity, whether it evicts or bypasses.
+    pub fn belady_warm(&self, capacity: usize, warmup: Warmup) -> HitStats {
+        let mut stats = HitStats::default();
+        let mut resident: HashMap<u64, usize> = HashMap::with_capacity(capacity);
+        let mut by_next_use: BTreeSet<(usize, u64)> = BTreeSet::new();
+        let warmup_events = warmup.events(self.accesses.len() as u64);
+        let mut full = false;
+        let mut classifier = MissClassifier::default();
+
+        for (idx, access) in self.accesses.iter().enumerate() {
+            let counting = match warmup_events {
+                Some(n) => idx as u64 >= n,
+                None => full,
+            };
+            // Warmup events update a scratch copy that is thrown away.
+            let mut scratch = HitStats::default();
+            let stats = if counting { &mut stats } else { &mut scratch };
+            let key = access.key;
+            if access.op == Op::Delete {
+                if let Some(next) = resident.remove(&key) {
+                    by_next_use.remove(&(next, key));
+                }
+                classifier.invalidate(key);
+                continue;
+            }
+            let miss_kind = classifier.miss_kind(key);
+            classifier.reference(key);
+
+            if let Some(next) = resident.get_mut(&key) {
+                by_next_use.remove(&(*next, key));
+                *next = access.next_use;
+                by_next_use.insert((access.next_use, key));
+                if access.op == Op::Get {
+                    stats.hits += 1;
+                } else {
+                    stats.updates += 1;
+                }
+                continue;
+            }
+
+            if access.op == Op::Get {
+                stats.misses += 1;
+                stats.record_miss(miss_kind);
+            }
+            if resident.len() >= capacity {
+                full = true;
+            }
+            if capacity == 0 || access.next_use == NEVER {
+                stats.bypasses += 1;
+                continue;
+            }
+            if resident.len() >= capacity {
+                let &(farthest, victim) = by_next_use.last().expect("cache is full");
+                if farthest <= access.next_use {
+                    stats.bypasses += 1;
+                    continue;
+                }
+                by_next_use.pop_last();
+                resident.remove(&victim);
+                stats.evictions += 1;
+            }
+            resident.insert(key, access.next_use);
+            by_next_use.insert((access.next_use, key));
+            stats.inserts += 1;
+        }
+
+        stats
+    }
+
+    /// Replay a size-aware MIN with room for `capacity_bytes`.
+    ///
+    /// Uses each event's weight (default 1) as its size. On a miss, the
+    /// objects used farthest in the future are evicted to make room, but only
+    /// if all of them are used later than the incoming object; otherwise the
+    /// object bypasses the cache. Optimal variable-size caching is NP-hard, so
+    /// this is a strong heuristic bound rather than the exact optimum. Byte
+    /// hit ratio is reported via `hit_bytes`/`miss_bytes`.
+    pub fn belady_weighted(&self, capacity_bytes: u64) -> HitStats {
+        let mut stats = HitStats::default();
+        let mut resident: HashMap<u64, (usize, u32)> = HashMap::new();
+        let mut by_next_use: BTreeSet<(usize, u64)> = BTreeSet::new();
+        let mut used = 0u64;
+        let mut victims = Vec::new();
+
+        for access in &self.accesses {
+            let key = access.key;
+            let weight = access.weight as u64;
+            if access.op == Op::Delete {
+                if let Some((next, size)) = resident.remove(&key) {
+                    by_next_use.remove(&(next, key));
+                    used -= size as u64;
+                }
+                continue;
+            }
+
+            if let Some((next, _)) = resident.get_mut(&key) {
+                by_next_use.remove(&(*next, key));
+                *next = access.next_use;
+                by_next_use.insert((access.next_use, key));
+                if access.op == Op::Get {
+                    stats.hits += 1;
+                    stats.hit_bytes += weight;
+                } else {
+                    stats.updates += 1;
+                }
+                continue;
+            }
+
+            if access.op == Op::Get {
+                stats.misses += 1;
+                stats.miss_bytes += weight;
+            }
+            if access.next_use == NEVER || weight > capacity_bytes {
+                stats.bypasses += 1;
+                continue;
+            }
+
+            // Collect victims farther in the future until the object fits.
+            victims.clear();
+            let mut freed = 0u64;
+            for &(next, victim) in by_next_use.iter().rev() {
+                if used - freed + weight <= capacity_bytes || next <= access.next_use {
+                    break;
+                }
+                freed += resident[&victim].1 as u64;
+                victims.push((next, victim));
+            }
+            if used - freed + weight > capacity_bytes {
+                stats.bypasses += 1;
+                continue;
+            }
+            for &(next, victim) in &victims {
+                by_next_use.remove(&(next, victim));
+                resident.remove(&victim);
+                stats.evictions += 1;
+            }
+            used = used - freed + weight;
+            resident.insert(key, (access.next_use, access.weight));
+            by_next_use.insert((access.next_use, key));
+            stats.inserts += 1;
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+    use crate::policies::Lru;
+    use crate::simulate;
+    use crate::workload::{BoundedGenerator, Workload, WorkloadSpec};
+
+    fn trace(keys: &[u64]) -> OfflineTrace {
+        let mut source = keys.iter().map(|&k| Event::get(k));
+        OfflineTrace::from_source(&mut source)
+    }
+
+    #[test]
+    fn test_belady_textbook_example() {
+        // Classic reference string: demand-paging MIN incurs 9 misses with 3
+        // frames; bypassing the never-reused key 4 saves one more.
+        let keys = [7, 0, 1, 2, 0, 3, 0, 4, 2, 3, 0, 3, 2, 1, 2, 0, 1, 7, 0, 1];
+        let opt = trace(&keys);
+        let stats = opt.belady(3);
+        assert_eq!(stats.misses, 8);
+        assert_eq!(stats.hits, 12);
+        // Every miss is either admitted or bypassed.
+        assert_eq!(stats.inserts + stats.bypasses, stats.misses);
+        assert_eq!(stats.bypasses, 2);
+        assert_eq!(opt.unique_keys(), 6);
+    }
+
+    #[test]
+    fn test_belady_bounds_lru() {
+        let spec = WorkloadSpec::new(1_000, Workload::Zipfian { exponent: 0.8 }, 3);
+        let mut source = BoundedGenerator::new(spec.generator(), 20_000);
+        let opt = OfflineTrace::from_source(&mut source);
+        let mut source = BoundedGenerator::new(spec.generator(), 20_000);
+        let lru = simulate(&mut Lru::new(100), &mut source);
+
+        assert!(opt.belady(100).hit_rate() >= lru.hit_rate());
+    }
+
+    #[test]
+    fn test_belady_delete_invalidates() {
+        let events = vec![Event::get(1), Event::delete(1), Event::get(1)];
+        let opt = OfflineTrace::from_source(&mut events.into_iter());
+        let stats = opt.belady(10);
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 2);
+    }
+
+    #[test]
+    fn test_belady_weighted_prefers_small_reused_objects() {
+        let events = vec![
+            Event::get(1).with_weight(60),
+            Event::get(2).with_weight(30),
+            Event::get(3).with_weight(30),
+            Event::get(2).with_weight(30),
+            Event::get(3).with_weight(30),
+            Event::get(1).with_weight(60),
+        ];
+        let opt = OfflineTrace::from_source(&mut events.into_iter());
+        let stats = opt.belady_weighted(100);
+
+        // Key 1 is evicted for 3 (which is needed sooner), then misses again.
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.hit_bytes, 60);
+        assert_eq!(stats.miss_bytes, 180);
+    }
+
+    #[test]
+    fn test_belady_warm_skips_cold_misses() {
+        let keys = [7, 0, 1, 2, 0, 3, 0, 4, 2, 3, 0, 3, 2, 1, 2, 0, 1, 7, 0, 1];
+        let opt = trace(&keys);
+        let full = opt.belady(3);
+        let warm = opt.belady_warm(3, Warmup::Events(4));
+
+        // The first four accesses are the cold misses of 7, 0, 1 and 2.
+        assert_eq!(warm.misses, full.misses - 4);
+        assert_eq!(warm.hits, full.hits);
+        assert_eq!(
+            opt.belady_warm(3, Warmup::Fraction(1.0)),
+            HitStats::default()
+        );
+    }
+
+    #[test]
+    fn test_belady_warm_until_full_ends_on_bypass() {
+        // One-shot keys 3..=10 are always bypassed, so MIN never evicts.
+        let keys = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1, 2];
+        let warm = trace(&keys).belady_warm(2, Warmup::UntilFull);
+
+        // Warmup ends with key 3, the first miss at capacity.
+        assert_eq!((warm.hits, warm.misses, warm.evictions), (2, 7, 0));
+    }
+}
@@ -0,0 +1,89 @@
+//! First In, First Out (FIFO).
+//!
+//! Evicts entries in insertion order. Hits do not change an entry's position.
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Reference FIFO cache with O(1) operations.
+#[derive(Debug, Clone)]
+pub struct Fifo {
+    capacity: usize,
+    queue: KeyList,
+    evicted: Vec<u64>,
+}
+
+impl Fifo {
+    /// Create a FIFO cache holding up to `capacity` entries (minimum 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            queue: KeyList::with_capacity(capacity),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl CacheModel for Fifo {
+    fn get(&mut self, key: u64) -> bool {
+        self.queue.contains(key)
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.queue.contains(key) {
+            return;
+        }
+        if self.queue.len() >= self.capacity {
+            if let Some(victim) = self.queue.pop_back() {
+                self.evicted.push(victim);
+            }
+        }
+        self.queue.push_front(key);
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.queue.contains(key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        self.queue.remove(key);
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_ignores_recency() {
+        let mut cache = Fifo::new(2);
+        cache.insert(1);
+        cache.insert(2);
+        assert!(cache.get(1));
+        cache.insert(3);
+
+        assert!(!cache.get(1));
+        assert!(cache.get(2));
+        assert!(cache.get(3));
+    }
+}
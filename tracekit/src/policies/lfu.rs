@@ -0,0 +1,239 @@
+//! Least Frequently Used (LFU).
+//!
+//! Evicts the entry with the fewest accesses, breaking ties by recency. Uses
+//! the constant-time frequency-bucket layout: each access count owns an LRU
+//! list, and the non-empty buckets are linked in count order, so the minimum
+//! is always the head of that chain.
+
+use std::collections::HashMap;
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Keys with one access count, linked to the neighboring non-empty counts.
+#[derive(Debug, Clone)]
+struct Bucket {
+    keys: KeyList,
+    prev: Option<u64>,
+    next: Option<u64>,
+}
+
+/// Reference LFU cache with O(1) operations.
+#[derive(Debug, Clone)]
+pub struct Lfu {
+    capacity: usize,
+    counts: HashMap<u64, u64>,
+    buckets: HashMap<u64, Bucket>,
+    /// Smallest count with a non-empty bucket.
+    min_count: Option<u64>,
+    evicted: Vec<u64>,
+}
+
+impl Lfu {
+    /// Create an LFU cache holding up to `capacity` entries (minimum 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            counts: HashMap::with_capacity(capacity),
+            buckets: HashMap::new(),
+            min_count: None,
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Push `key` onto the bucket for `count`, creating the bucket right after
+    /// `prev` (or at the head of the chain) if needed.
+    ///
+    /// `prev` must be the largest non-empty count below `count`.
+    fn link(&mut self, key: u64, count: u64, prev: Option<u64>) {
+        if !self.buckets.contains_key(&count) {
+            let next = match prev {
+                Some(prev) => self.buckets[&prev].next,
+                None => self.min_count,
+            };
+            match prev {
+                Some(prev) => self.buckets.get_mut(&prev).unwrap().next = Some(count),
+                None => self.min_count = Some(count),
+            }
+            if let Some(next) = next {
+                self.buckets.get_mut(&next).unwrap().prev = Some(count);
+            }
+            self.buckets.insert(
+                count,
+                Bucket {
+                    keys: KeyList::new(),
+                    prev,
+                    next,
+                },
+            );
+        }
+        self.buckets.get_mut(&count).unwrap().keys.push_front(key);
+    }
+
+    /// Remove `key` from the bucket for `count`, dropping the bucket from the
+    /// chain if it empties.
+    fn unlink(&mut self, key: u64, count: u64) {
+        let Some(bucket) = self.buckets.get_mut(&count) else {
+            return;
+        };
+        bucket.keys.remove(key);
+        if bucket.keys.is_empty() {
+            self.drop_bucket(count);
+        }
+    }
+
+    fn drop_bucket(&mut self, count: u64) {
+        let Some(Bucket { prev, next, .. }) = self.buckets.remove(&count) else {
+            return;
+        };
+        match prev {
+            Some(prev) => self.buckets.get_mut(&prev).unwrap().next = next,
+            None => self.min_count = next,
+        }
+        if let Some(next) = next {
+            self.buckets.get_mut(&next).unwrap().prev = prev;
+        }
+    }
+
+    fn touch(&mut self, key: u64) -> bool {
+        let Some(count) = self.counts.get_mut(&key) else {
+            return false;
+        };
+        let old = *count;
+        *count += 1;
+        // Link the next bucket while the old one still anchors its position.
+        self.link(key, old + 1, Some(old));
+        self.unlink(key, old);
+        true
+    }
+
+    fn evict(&mut self) {
+        let Some(min) = self.min_count else {
+            return;
+        };
+        let bucket = self.buckets.get_mut(&min).unwrap();
+        if let Some(victim) = bucket.keys.pop_back() {
+            if bucket.keys.is_empty() {
+                self.drop_bucket(min);
+            }
+            self.counts.remove(&victim);
+            self.evicted.push(victim);
+        }
+    }
+}
+
+impl CacheModel for Lfu {
+    fn get(&mut self, key: u64) -> bool {
+        self.touch(key)
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.touch(key) {
+            return;
+        }
+        if self.counts.len() >= self.capacity {
+            self.evict();
+        }
+        self.counts.insert(key, 1);
+        self.link(key, 1, None);
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.counts.contains_key(&key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        if let Some(count) = self.counts.remove(&key) {
+            self.unlink(key, count);
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfu_evicts_least_frequent() {
+        let mut cache = Lfu::new(2);
+        cache.insert(1);
+        cache.insert(2);
+        assert!(cache.get(1));
+        assert!(cache.get(1));
+        assert!(cache.get(2));
+        cache.insert(3);
+
+        assert!(cache.get(1));
+        assert!(!cache.get(2));
+        assert!(cache.get(3));
+    }
+
+    #[test]
+    fn test_lfu_ties_break_by_recency() {
+        let mut cache = Lfu::new(2);
+        cache.insert(1);
+        cache.insert(2);
+        cache.insert(3);
+
+        assert!(!cache.get(1));
+        assert!(cache.get(2));
+    }
+
+    #[test]
+    fn test_lfu_delete_frees_space() {
+        let mut cache = Lfu::new(2);
+        cache.insert(1);
+        cache.insert(2);
+        assert!(cache.get(2));
+        cache.delete(1);
+        cache.insert(3);
+
+        assert!(cache.get(2));
+        assert!(cache.get(3));
+        let mut evicted = Vec::new();
+        cache.drain_evictions(&mut evicted);
+        assert!(evicted.is_empty());
+    }
+
+    #[test]
+    fn test_lfu_delete_of_minimum_keeps_order() {
+        let mut cache = Lfu::new(3);
+        for key in [1, 2, 3] {
+            cache.insert(key);
+        }
+        // Counts: 1 -> 2, 2 -> 3, 3 -> 1.
+        assert!(cache.get(1));
+        assert!(cache.get(2));
+        assert!(cache.get(2));
+        // Deleting the only count-1 key leaves count 2 as the minimum.
+        cache.delete(3);
+        assert_eq!(cache.min_count, Some(2));
+        cache.insert(4);
+        cache.insert(5);
+
+        let mut evicted = Vec::new();
+        cache.drain_evictions(&mut evicted);
+        assert_eq!(evicted, vec![4]);
+        assert!(cache.get(1) && cache.get(2) && cache.get(5));
+    }
+}
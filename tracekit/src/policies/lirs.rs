@@ -0,0 +1,257 @@
+//! Low Inter-reference Recency Set (Jiang & Zhang, SIGMETRICS 2002).
+//!
+//! Entries are classified by inter-reference recency: LIR entries (reused
+//! recently) hold most of the capacity, while a small HIR queue holds resident
+//! entries on probation. The recency stack `S` also remembers non-resident HIR
+//! keys so that a quick reuse can promote them to LIR.
+
+use std::collections::HashMap;
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Default fraction of capacity reserved for resident HIR entries.
+pub const DEFAULT_HIR_RATIO: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Lir,
+    HirResident,
+    HirNonResident,
+}
+
+/// Reference LIRS cache with O(1) amortized operations.
+///
+/// Non-resident HIR metadata is bounded to `capacity` keys.
+#[derive(Debug, Clone)]
+pub struct Lirs {
+    capacity: usize,
+    lir_limit: usize,
+    hir_limit: usize,
+    lir_count: usize,
+    /// Recency stack; front is the top.
+    stack: KeyList,
+    /// Resident HIR queue; back is the next victim.
+    queue: KeyList,
+    /// Non-resident HIR keys still in the stack, oldest at the back.
+    ghosts: KeyList,
+    status: HashMap<u64, Status>,
+    evicted: Vec<u64>,
+}
+
+impl Lirs {
+    /// Create a LIRS cache with the default HIR ratio.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hir_ratio(capacity, DEFAULT_HIR_RATIO)
+    }
+
+    /// Create a LIRS cache reserving `hir_ratio` of capacity for resident HIR entries.
+    pub fn with_hir_ratio(capacity: usize, hir_ratio: f64) -> Self {
+        let capacity = capacity.max(1);
+        let hir_limit =
+            ((capacity as f64 * hir_ratio.clamp(0.0, 1.0)).round() as usize).clamp(1, capacity);
+        Self {
+            capacity,
+            lir_limit: capacity - hir_limit,
+            hir_limit,
+            lir_count: 0,
+            stack: KeyList::with_capacity(capacity),
+            queue: KeyList::with_capacity(hir_limit),
+            ghosts: KeyList::new(),
+            status: HashMap::with_capacity(capacity),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.lir_count + self.queue.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push_stack(&mut self, key: u64) {
+        if !self.stack.move_to_front(key) {
+            self.stack.push_front(key);
+        }
+    }
+
+    /// Pop non-LIR entries off the bottom of the stack.
+    fn prune(&mut self) {
+        while let Some(bottom) = self.stack.back() {
+            match self.status.get(&bottom) {
+                Some(Status::Lir) => break,
+                Some(Status::HirNonResident) => {
+                    self.status.remove(&bottom);
+                    self.ghosts.remove(bottom);
+                }
+                _ => {}
+            }
+            self.stack.pop_back();
+        }
+    }
+
+    /// Turn the bottom LIR entry into a resident HIR entry.
+    fn demote_bottom(&mut self) {
+        self.prune();
+        if let Some(bottom) = self.stack.pop_back() {
+            debug_assert_eq!(self.status.get(&bottom), Some(&Status::Lir));
+            self.status.insert(bottom, Status::HirResident);
+            self.queue.push_front(bottom);
+            self.lir_count -= 1;
+        }
+        self.prune();
+    }
+
+    /// Promote `key` (currently HIR and in the stack) to LIR.
+    fn promote(&mut self, key: u64) {
+        self.status.insert(key, Status::Lir);
+        self.stack.move_to_front(key);
+        self.lir_count += 1;
+        self.demote_bottom();
+    }
+
+    fn remember_ghost(&mut self, key: u64) {
+        self.status.insert(key, Status::HirNonResident);
+        self.ghosts.push_front(key);
+        if self.ghosts.len() > self.capacity {
+            if let Some(old) = self.ghosts.pop_back() {
+                self.status.remove(&old);
+                self.stack.remove(old);
+                self.prune();
+            }
+        }
+    }
+}
+
+impl CacheModel for Lirs {
+    fn get(&mut self, key: u64) -> bool {
+        match self.status.get(&key) {
+            Some(Status::Lir) => {
+                self.stack.move_to_front(key);
+                self.prune();
+                true
+            }
+            Some(Status::HirResident) => {
+                if self.stack.contains(key) {
+                    self.queue.remove(key);
+                    self.promote(key);
+                } else {
+                    self.stack.push_front(key);
+                    self.queue.move_to_front(key);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.get(key) {
+            return;
+        }
+
+        // Warm-up: the first distinct keys fill the LIR set directly.
+        if self.lir_count < self.lir_limit {
+            if self.status.get(&key) == Some(&Status::HirNonResident) {
+                self.ghosts.remove(key);
+            }
+            self.status.insert(key, Status::Lir);
+            self.push_stack(key);
+            self.lir_count += 1;
+            return;
+        }
+
+        if self.queue.len() >= self.hir_limit {
+            if let Some(victim) = self.queue.pop_back() {
+                self.evicted.push(victim);
+                if self.stack.contains(victim) {
+                    self.remember_ghost(victim);
+                } else {
+                    self.status.remove(&victim);
+                }
+            }
+        }
+
+        if self.status.get(&key) == Some(&Status::HirNonResident) {
+            self.ghosts.remove(key);
+            self.promote(key);
+        } else {
+            self.status.insert(key, Status::HirResident);
+            self.push_stack(key);
+            self.queue.push_front(key);
+        }
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        matches!(
+            self.status.get(&key),
+            Some(Status::Lir | Status::HirResident)
+        )
+    }
+
+    fn delete(&mut self, key: u64) {
+        match self.status.remove(&key) {
+            Some(Status::Lir) => {
+                self.stack.remove(key);
+                self.lir_count -= 1;
+                self.prune();
+            }
+            Some(Status::HirResident) => {
+                self.queue.remove(key);
+                self.stack.remove(key);
+            }
+            Some(Status::HirNonResident) => {
+                self.ghosts.remove(key);
+                self.stack.remove(key);
+            }
+            None => {}
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lirs_protects_lir_set_from_scans() {
+        let mut cache = Lirs::with_hir_ratio(4, 0.25);
+        for key in 1..=3 {
+            cache.insert(key);
+        }
+        for key in 100..200 {
+            cache.insert(key);
+        }
+        for key in 1..=3 {
+            assert!(cache.get(key));
+        }
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn test_lirs_ghost_reuse_promotes() {
+        let mut cache = Lirs::with_hir_ratio(3, 0.34);
+        cache.insert(1);
+        cache.insert(2);
+        cache.insert(3);
+        cache.insert(4);
+        // Key 3 was evicted but is still in the stack as a non-resident HIR.
+        assert!(!cache.get(3));
+        cache.insert(3);
+        assert_eq!(cache.status.get(&3), Some(&Status::Lir));
+        assert_eq!(cache.len(), 3);
+    }
+}
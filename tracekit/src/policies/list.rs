@@ -0,0 +1,197 @@
+//! Intrusive ordered key set shared by the reference policies.
+//!
+//! ## Architecture
+//! `KeyList` is a doubly-linked list stored in a slab (`Vec`) with a
+//! `HashMap` from key to slot, giving O(1) push, removal and reordering by key.
+//! The front holds the most recently pushed key, the back the oldest.
+
+use std::collections::HashMap;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    key: u64,
+    prev: usize,
+    next: usize,
+}
+
+/// Ordered set of keys with O(1) push, removal and move-to-front.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyList {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    index: HashMap<u64, usize>,
+    head: usize,
+    tail: usize,
+}
+
+impl Default for KeyList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyList {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            index: HashMap::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, key: u64) -> bool {
+        self.index.contains_key(&key)
+    }
+
+    /// Oldest key, if any.
+    #[inline]
+    pub(crate) fn back(&self) -> Option<u64> {
+        (self.tail != NIL).then(|| self.nodes[self.tail].key)
+    }
+
+    /// Key pushed immediately after `key` (one step towards the front).
+    pub(crate) fn newer(&self, key: u64) -> Option<u64> {
+        let idx = *self.index.get(&key)?;
+        let prev = self.nodes[idx].prev;
+        (prev != NIL).then(|| self.nodes[prev].key)
+    }
+
+    /// Push `key` at the front. The key must not already be present.
+    pub(crate) fn push_front(&mut self, key: u64) {
+        debug_assert!(!self.contains(key), "key {key} already in list");
+        let node = Node {
+            key,
+            prev: NIL,
+            next: NIL,
+        };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.index.insert(key, idx);
+        self.link_front(idx);
+    }
+
+    /// Remove and return the oldest key.
+    pub(crate) fn pop_back(&mut self) -> Option<u64> {
+        let key = self.back()?;
+        self.remove(key);
+        Some(key)
+    }
+
+    /// Remove `key`, returning whether it was present.
+    pub(crate) fn remove(&mut self, key: u64) -> bool {
+        match self.index.remove(&key) {
+            Some(idx) => {
+                self.unlink(idx);
+                self.free.push(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move `key` to the front, returning whether it was present.
+    pub(crate) fn move_to_front(&mut self, key: u64) -> bool {
+        match self.index.get(&key) {
+            Some(&idx) => {
+                if idx != self.head {
+                    self.unlink(idx);
+                    self.link_front(idx);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn link_front(&mut self, idx: usize) {
+        self.nodes[idx].prev = NIL;
+        self.nodes[idx].next = self.head;
+        if self.head != NIL {
+            self.nodes[self.head].prev = idx;
+        } else {
+            self.tail = idx;
+        }
+        self.head = idx;
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let Node { prev, next, .. } = self.nodes[idx];
+        if prev != NIL {
+            self.nodes[prev].next = next;
+        } else {
+            self.head = next;
+        }
+        if next != NIL {
+            self.nodes[next].prev = prev;
+        } else {
+            self.tail = prev;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_list_order() {
+        let mut list = KeyList::new();
+        list.push_front(1);
+        list.push_front(2);
+        list.push_front(3);
+        assert_eq!(list.back(), Some(1));
+        assert_eq!(list.newer(1), Some(2));
+        assert_eq!(list.newer(3), None);
+
+        assert!(list.move_to_front(1));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(1));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_key_list_remove_and_reuse() {
+        let mut list = KeyList::new();
+        list.push_front(1);
+        list.push_front(2);
+        list.push_front(3);
+        assert!(list.remove(2));
+        assert!(!list.remove(2));
+        assert_eq!(list.newer(1), Some(3));
+
+        list.push_front(4);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.nodes.len(), 3);
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(4));
+    }
+}
@@ -0,0 +1,103 @@
+//! Least Recently Used (LRU).
+//!
+//! Evicts the entry that has gone longest without being accessed. Hits and
+//! re-inserts move the entry to the most-recently-used position.
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Reference LRU cache with O(1) operations.
+#[derive(Debug, Clone)]
+pub struct Lru {
+    capacity: usize,
+    list: KeyList,
+    evicted: Vec<u64>,
+}
+
+impl Lru {
+    /// Create an LRU cache holding up to `capacity` entries (minimum 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            list: KeyList::with_capacity(capacity),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl CacheModel for Lru {
+    fn get(&mut self, key: u64) -> bool {
+        self.list.move_to_front(key)
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.list.move_to_front(key) {
+            return;
+        }
+        if self.list.len() >= self.capacity {
+            if let Some(victim) = self.list.pop_back() {
+                self.evicted.push(victim);
+            }
+        }
+        self.list.push_front(key);
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.list.contains(key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        self.list.remove(key);
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut cache = Lru::new(2);
+        cache.insert(1);
+        cache.insert(2);
+        assert!(cache.get(1));
+        cache.insert(3);
+
+        assert!(cache.get(1));
+        assert!(!cache.get(2));
+        assert!(cache.get(3));
+
+        let mut evicted = Vec::new();
+        cache.drain_evictions(&mut evicted);
+        assert_eq!(evicted, vec![2]);
+    }
+
+    #[test]
+    fn test_lru_delete() {
+        let mut cache = Lru::new(2);
+        cache.insert(1);
+        cache.delete(1);
+        assert!(!cache.get(1));
+        assert!(cache.is_empty());
+    }
+}
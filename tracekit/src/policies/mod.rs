@@ -0,0 +1,76 @@
+//! Reference cache policies implementing [`CacheModel`](crate::CacheModel).
+//!
+//! ## Architecture
+//! Each policy is a straightforward, O(1) (amortized) implementation of a
+//! well-known algorithm, intended as a baseline when evaluating other caches.
+//! Policies count capacity in entries, ignore weights, and report evicted keys
+//! through [`CacheModel::drain_evictions`](crate::CacheModel::drain_evictions).
+//!
+//! ## Key Components
+//! - [`Lru`], [`Fifo`], [`Clock`]: recency and insertion-order baselines
+//! - [`Lfu`]: frequency-based eviction with LRU tie-breaking
+//! - [`TwoQ`], [`ArcCache`], [`Lirs`]: scan-resistant adaptive policies
+//! - [`S3Fifo`], [`Sieve`]: FIFO-based policies with lazy promotion
+//! - [`TinyLfu`]: W-TinyLFU admission with a frequency sketch
+
+mod list;
+
+pub mod arc;
+pub mod clock;
+pub mod fifo;
+pub mod lfu;
+pub mod lirs;
+pub mod lru;
+pub mod s3_fifo;
+pub mod sieve;
+pub mod tiny_lfu;
+pub mod two_q;
+
+pub use arc::ArcCache;
+pub use clock::Clock;
+pub use fifo::Fifo;
+pub use lfu::Lfu;
+pub use lirs::Lirs;
+pub use lru::Lru;
+pub use s3_fifo::S3Fifo;
+pub use sieve::Sieve;
+pub use tiny_lfu::TinyLfu;
+pub use two_q::TwoQ;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CacheModel;
+    use crate::simulate;
+    use crate::workload::{BoundedGenerator, Workload, WorkloadSpec};
+
+    /// Run every policy against a skewed trace with deletes and check the
+    /// capacity bound and eviction bookkeeping.
+    fn check_invariants<C: CacheModel>(mut cache: C, len: impl Fn(&C) -> usize) {
+        let spec = WorkloadSpec::new(2_000, Workload::Zipfian { exponent: 0.9 }, 7);
+        let mut source = BoundedGenerator::new(spec.generator(), 20_000);
+        let stats = simulate(&mut cache, &mut source);
+        assert!(len(&cache) <= 100);
+        assert!(stats.hits > 0);
+        assert_eq!(stats.inserts - stats.evictions, len(&cache) as u64);
+
+        for key in 0..2_000 {
+            cache.delete(key);
+        }
+        assert_eq!(len(&cache), 0);
+    }
+
+    #[test]
+    fn test_policies_respect_capacity() {
+        check_invariants(Lru::new(100), Lru::len);
+        check_invariants(Fifo::new(100), Fifo::len);
+        check_invariants(Clock::new(100), Clock::len);
+        check_invariants(Lfu::new(100), Lfu::len);
+        check_invariants(TwoQ::new(100), TwoQ::len);
+        check_invariants(ArcCache::new(100), ArcCache::len);
+        check_invariants(Lirs::new(100), Lirs::len);
+        check_invariants(S3Fifo::new(100), S3Fifo::len);
+        check_invariants(Sieve::new(100), Sieve::len);
+        check_invariants(TinyLfu::new(100), TinyLfu::len);
+    }
+}
@@ -0,0 +1,215 @@
+//! S3-FIFO (Yang et al., SOSP 2023).
+//!
+//! Three FIFO queues: a small probation queue `S` (10% of capacity), a main
+//! queue `M` and a ghost queue `G` of recently evicted keys. Entries leave `S`
+//! for `M` only if they were reused while on probation, so one-hit wonders are
+//! filtered out quickly. `M` behaves like CLOCK with a 2-bit frequency.
+
+use std::collections::HashMap;
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Default fraction of capacity used by the small probation queue.
+pub const DEFAULT_SMALL_RATIO: f64 = 0.1;
+
+/// Saturation value of the per-entry access counter.
+const MAX_FREQ: u8 = 3;
+
+/// Reference S3-FIFO cache with O(1) amortized operations.
+#[derive(Debug, Clone)]
+pub struct S3Fifo {
+    capacity: usize,
+    small_capacity: usize,
+    main_capacity: usize,
+    small: KeyList,
+    main: KeyList,
+    ghost: KeyList,
+    freq: HashMap<u64, u8>,
+    evicted: Vec<u64>,
+}
+
+impl S3Fifo {
+    /// Create an S3-FIFO cache with the default 10% small queue.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_small_ratio(capacity, DEFAULT_SMALL_RATIO)
+    }
+
+    /// Create an S3-FIFO cache whose small queue holds `small_ratio` of capacity.
+    pub fn with_small_ratio(capacity: usize, small_ratio: f64) -> Self {
+        let capacity = capacity.max(1);
+        let small_capacity =
+            ((capacity as f64 * small_ratio.clamp(0.0, 1.0)).round() as usize).clamp(1, capacity);
+        let main_capacity = (capacity - small_capacity).max(1);
+        Self {
+            capacity,
+            small_capacity,
+            main_capacity,
+            small: KeyList::with_capacity(small_capacity),
+            main: KeyList::with_capacity(main_capacity),
+            ghost: KeyList::with_capacity(main_capacity),
+            freq: HashMap::with_capacity(capacity),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.freq.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.freq.is_empty()
+    }
+
+    fn evict(&mut self) {
+        if self.small.len() >= self.small_capacity || self.main.is_empty() {
+            self.evict_small();
+        } else {
+            self.evict_main();
+        }
+    }
+
+    fn evict_small(&mut self) {
+        while let Some(key) = self.small.pop_back() {
+            if self.freq.get(&key).copied().unwrap_or(0) > 1 {
+                self.main.push_front(key);
+                // Promoting into a full main queue frees the slot there.
+                if self.main.len() > self.main_capacity {
+                    self.evict_main();
+                    return;
+                }
+            } else {
+                self.freq.remove(&key);
+                self.ghost.push_front(key);
+                // The ghost queue remembers as many keys as the main queue holds.
+                if self.ghost.len() > self.main_capacity {
+                    self.ghost.pop_back();
+                }
+                self.evicted.push(key);
+                return;
+            }
+        }
+    }
+
+    fn evict_main(&mut self) {
+        while let Some(key) = self.main.pop_back() {
+            match self.freq.get_mut(&key) {
+                Some(freq) if *freq > 0 => {
+                    *freq -= 1;
+                    self.main.push_front(key);
+                }
+                _ => {
+                    self.freq.remove(&key);
+                    self.evicted.push(key);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl CacheModel for S3Fifo {
+    fn get(&mut self, key: u64) -> bool {
+        match self.freq.get_mut(&key) {
+            Some(freq) => {
+                *freq = (*freq + 1).min(MAX_FREQ);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.get(key) {
+            return;
+        }
+        while self.freq.len() >= self.capacity {
+            self.evict();
+        }
+        if self.ghost.remove(key) {
+            self.main.push_front(key);
+        } else {
+            self.small.push_front(key);
+        }
+        self.freq.insert(key, 0);
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.freq.contains_key(&key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        if self.freq.remove(&key).is_some() {
+            if !self.small.remove(key) {
+                self.main.remove(key);
+            }
+        } else {
+            self.ghost.remove(key);
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s3_fifo_filters_one_hit_wonders() {
+        let mut cache = S3Fifo::new(10);
+        for key in 0..9 {
+            cache.insert(key);
+            cache.get(key);
+            cache.get(key);
+        }
+        for key in 100..200 {
+            cache.insert(key);
+        }
+        for key in 0..9 {
+            assert!(
+                cache.get(key),
+                "key {key} should have been promoted to main"
+            );
+        }
+        assert_eq!(cache.len(), 10);
+    }
+
+    #[test]
+    fn test_s3_fifo_ghost_hit_enters_main() {
+        let mut cache = S3Fifo::new(10);
+        for key in 0..11 {
+            cache.insert(key);
+        }
+        // Key 0 was evicted from the small queue into the ghost queue.
+        assert!(!cache.get(0));
+        cache.insert(0);
+        assert!(cache.main.contains(0));
+    }
+
+    #[test]
+    fn test_s3_fifo_insert_evicts_exactly_one() {
+        let mut cache = S3Fifo::with_small_ratio(10, 0.5);
+        for key in 0..10 {
+            cache.insert(key);
+            cache.get(key);
+            cache.get(key);
+        }
+        // Every small entry is promoted, overflowing the main queue mid-way.
+        cache.insert(10);
+
+        let mut evicted = Vec::new();
+        cache.drain_evictions(&mut evicted);
+        assert_eq!(evicted.len(), 1, "{evicted:?}");
+        assert_eq!(cache.len(), 10);
+    }
+}
@@ -0,0 +1,146 @@
+//! SIEVE (Zhang et al., NSDI 2024).
+//!
+//! A FIFO queue with a visited bit and a hand. Hits only set the bit. On
+//! eviction the hand moves from the oldest entry towards the newest, clearing
+//! visited bits and evicting the first unvisited entry; the hand then stays in
+//! place, so survivors keep their queue position (lazy promotion).
+
+use std::collections::HashMap;
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Reference SIEVE cache with O(1) amortized operations.
+#[derive(Debug, Clone)]
+pub struct Sieve {
+    capacity: usize,
+    queue: KeyList,
+    visited: HashMap<u64, bool>,
+    hand: Option<u64>,
+    evicted: Vec<u64>,
+}
+
+impl Sieve {
+    /// Create a SIEVE cache holding up to `capacity` entries (minimum 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            queue: KeyList::with_capacity(capacity),
+            visited: HashMap::with_capacity(capacity),
+            hand: None,
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn evict(&mut self) {
+        let mut cursor = self.hand.or_else(|| self.queue.back());
+        while let Some(key) = cursor {
+            let visited = self
+                .visited
+                .get_mut(&key)
+                .expect("queued key has a visited bit");
+            if *visited {
+                *visited = false;
+                cursor = self.queue.newer(key).or_else(|| self.queue.back());
+            } else {
+                self.hand = self.queue.newer(key);
+                self.queue.remove(key);
+                self.visited.remove(&key);
+                self.evicted.push(key);
+                return;
+            }
+        }
+    }
+}
+
+impl CacheModel for Sieve {
+    fn get(&mut self, key: u64) -> bool {
+        match self.visited.get_mut(&key) {
+            Some(visited) => {
+                *visited = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.get(key) {
+            return;
+        }
+        if self.queue.len() >= self.capacity {
+            self.evict();
+        }
+        self.queue.push_front(key);
+        self.visited.insert(key, false);
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.visited.contains_key(&key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        if self.visited.remove(&key).is_some() {
+            if self.hand == Some(key) {
+                self.hand = self.queue.newer(key);
+            }
+            self.queue.remove(key);
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sieve_keeps_visited_entries() {
+        let mut cache = Sieve::new(3);
+        cache.insert(1);
+        cache.insert(2);
+        cache.insert(3);
+        assert!(cache.get(1));
+        cache.insert(4);
+
+        assert!(cache.get(1));
+        assert!(!cache.get(2));
+        assert!(cache.get(3));
+        assert!(cache.get(4));
+    }
+
+    #[test]
+    fn test_sieve_hand_resumes_after_eviction() {
+        let mut cache = Sieve::new(3);
+        cache.insert(1);
+        cache.insert(2);
+        cache.insert(3);
+        assert!(cache.get(1));
+        cache.insert(4); // evicts 2, hand -> 3
+        cache.insert(5); // evicts 3, hand -> 4
+
+        let mut evicted = Vec::new();
+        cache.drain_evictions(&mut evicted);
+        assert_eq!(evicted, vec![2, 3]);
+        assert!(cache.get(1));
+    }
+}
@@ -0,0 +1,248 @@
+//! W-TinyLFU (Einziger et al., ACM ToS 2017), as popularized by Caffeine.
+//!
+//! New entries land in a small LRU admission window (1% of capacity). When the
+//! window overflows, its victim competes with the main cache's victim and is
+//! admitted only if a Count-Min sketch estimates it to be more popular. The
+//! main cache is a segmented LRU with probation and protected segments.
+//! The sketch uses 4-bit counters and halves them periodically so that stale
+//! popularity decays.
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Default fraction of capacity used by the admission window.
+pub const DEFAULT_WINDOW_RATIO: f64 = 0.01;
+
+/// Fraction of the main cache reserved for the protected segment.
+const PROTECTED_RATIO: f64 = 0.8;
+
+/// Count-Min sketch with saturating 4-bit counters and periodic aging.
+#[derive(Debug, Clone)]
+struct FrequencySketch {
+    counters: Vec<u8>,
+    mask: usize,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    const DEPTH: usize = 4;
+    const MAX_COUNT: u8 = 15;
+    const SEEDS: [u64; Self::DEPTH] = [
+        0x9e37_79b9_7f4a_7c15,
+        0xbf58_476d_1ce4_e5b9,
+        0x94d0_49bb_1331_11eb,
+        0xd6e8_feb8_6659_fd93,
+    ];
+
+    fn new(capacity: usize) -> Self {
+        // Four counters per entry and row keeps collisions rare.
+        let width = (capacity.max(16) * 4).next_power_of_two();
+        Self {
+            counters: vec![0; width * Self::DEPTH],
+            mask: width - 1,
+            additions: 0,
+            sample_size: capacity.max(1) * 10,
+        }
+    }
+
+    #[inline]
+    fn slot(&self, key: u64, row: usize) -> usize {
+        let mut h = key ^ Self::SEEDS[row];
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+        row * (self.mask + 1) + (h as usize & self.mask)
+    }
+
+    fn increment(&mut self, key: u64) {
+        for row in 0..Self::DEPTH {
+            let idx = self.slot(key, row);
+            if self.counters[idx] < Self::MAX_COUNT {
+                self.counters[idx] += 1;
+            }
+        }
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            for counter in &mut self.counters {
+                *counter >>= 1;
+            }
+            self.additions /= 2;
+        }
+    }
+
+    fn frequency(&self, key: u64) -> u8 {
+        (0..Self::DEPTH)
+            .map(|row| self.counters[self.slot(key, row)])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// Reference W-TinyLFU cache with O(1) operations.
+#[derive(Debug, Clone)]
+pub struct TinyLfu {
+    capacity: usize,
+    window_capacity: usize,
+    main_capacity: usize,
+    protected_capacity: usize,
+    window: KeyList,
+    probation: KeyList,
+    protected: KeyList,
+    sketch: FrequencySketch,
+    /// Key of the last missed lookup, so the following insert is not counted twice.
+    pending_miss: Option<u64>,
+    evicted: Vec<u64>,
+}
+
+impl TinyLfu {
+    /// Create a W-TinyLFU cache with the default 1% admission window.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_window_ratio(capacity, DEFAULT_WINDOW_RATIO)
+    }
+
+    /// Create a W-TinyLFU cache whose window holds `window_ratio` of capacity.
+    pub fn with_window_ratio(capacity: usize, window_ratio: f64) -> Self {
+        let capacity = capacity.max(1);
+        let window_capacity =
+            ((capacity as f64 * window_ratio.clamp(0.0, 1.0)).round() as usize).clamp(1, capacity);
+        let main_capacity = capacity - window_capacity;
+        let protected_capacity = (main_capacity as f64 * PROTECTED_RATIO) as usize;
+        Self {
+            capacity,
+            window_capacity,
+            main_capacity,
+            protected_capacity,
+            window: KeyList::with_capacity(window_capacity),
+            probation: KeyList::with_capacity(main_capacity),
+            protected: KeyList::with_capacity(protected_capacity),
+            sketch: FrequencySketch::new(capacity),
+            pending_miss: None,
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.window.len() + self.probation.len() + self.protected.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn touch(&mut self, key: u64) -> bool {
+        if self.window.move_to_front(key) || self.protected.move_to_front(key) {
+            return true;
+        }
+        if self.probation.remove(key) {
+            self.protected.push_front(key);
+            if self.protected.len() > self.protected_capacity {
+                if let Some(demoted) = self.protected.pop_back() {
+                    self.probation.push_front(demoted);
+                }
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Move the window's victim into the main cache if the sketch admits it.
+    fn admit(&mut self, candidate: u64) {
+        if self.probation.len() + self.protected.len() < self.main_capacity {
+            self.probation.push_front(candidate);
+            return;
+        }
+        let victim = self.probation.back().or_else(|| self.protected.back());
+        match victim {
+            Some(victim) if self.sketch.frequency(candidate) > self.sketch.frequency(victim) => {
+                if !self.probation.remove(victim) {
+                    self.protected.remove(victim);
+                }
+                self.evicted.push(victim);
+                self.probation.push_front(candidate);
+            }
+            _ => self.evicted.push(candidate),
+        }
+    }
+}
+
+impl CacheModel for TinyLfu {
+    fn get(&mut self, key: u64) -> bool {
+        self.sketch.increment(key);
+        let hit = self.touch(key);
+        self.pending_miss = if hit { None } else { Some(key) };
+        hit
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.pending_miss.take() != Some(key) {
+            self.sketch.increment(key);
+        }
+        if self.touch(key) {
+            return;
+        }
+        self.window.push_front(key);
+        if self.window.len() > self.window_capacity {
+            if let Some(candidate) = self.window.pop_back() {
+                self.admit(candidate);
+            }
+        }
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.window.contains(key) || self.probation.contains(key) || self.protected.contains(key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        if !self.window.remove(key) && !self.probation.remove(key) {
+            self.protected.remove(key);
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sketch_estimates_frequency() {
+        let mut sketch = FrequencySketch::new(64);
+        for _ in 0..5 {
+            sketch.increment(42);
+        }
+        sketch.increment(7);
+        assert!(sketch.frequency(42) >= 5);
+        assert!(sketch.frequency(42) > sketch.frequency(7));
+    }
+
+    #[test]
+    fn test_tiny_lfu_rejects_unpopular_candidates() {
+        let mut cache = TinyLfu::new(100);
+        for _ in 0..5 {
+            for key in 0..99 {
+                if !cache.get(key) {
+                    cache.insert(key);
+                }
+            }
+        }
+        for key in 1000..2000 {
+            if !cache.get(key) {
+                cache.insert(key);
+            }
+        }
+        let retained = (0..99).filter(|&key| cache.get(key)).count();
+        assert!(retained >= 95, "only {retained} hot keys retained");
+        assert!(cache.len() <= 100);
+    }
+}
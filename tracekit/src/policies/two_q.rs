@@ -0,0 +1,162 @@
+//! 2Q (Johnson & Shasha, VLDB 1994).
+//!
+//! New entries enter a FIFO probation queue (`A1in`). Entries evicted from
+//! probation leave their key in a ghost queue (`A1out`); a miss on a ghost key
+//! admits it straight into the main LRU (`Am`). One-hit wonders therefore never
+//! displace the frequently used working set.
+
+use super::list::KeyList;
+use crate::model::CacheModel;
+
+/// Default fraction of capacity reserved for the `A1in` probation queue.
+pub const DEFAULT_KIN: f64 = 0.25;
+/// Default size of the `A1out` ghost queue as a fraction of capacity.
+pub const DEFAULT_KOUT: f64 = 0.5;
+
+/// Reference full-version 2Q cache with O(1) operations.
+#[derive(Debug, Clone)]
+pub struct TwoQ {
+    capacity: usize,
+    kin: usize,
+    kout: usize,
+    a1in: KeyList,
+    a1out: KeyList,
+    am: KeyList,
+    evicted: Vec<u64>,
+}
+
+impl TwoQ {
+    /// Create a 2Q cache with the paper's recommended queue sizes.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_ratios(capacity, DEFAULT_KIN, DEFAULT_KOUT)
+    }
+
+    /// Create a 2Q cache with custom queue sizes.
+    ///
+    /// `kin` is the fraction of capacity used by the `A1in` probation queue and
+    /// `kout` the number of ghost keys remembered, as a fraction of capacity.
+    pub fn with_ratios(capacity: usize, kin: f64, kout: f64) -> Self {
+        let capacity = capacity.max(1);
+        let kin = ((capacity as f64 * kin.clamp(0.0, 1.0)).round() as usize).max(1);
+        let kout = ((capacity as f64 * kout.max(0.0)).round() as usize).max(1);
+        Self {
+            capacity,
+            kin,
+            kout,
+            a1in: KeyList::with_capacity(kin),
+            a1out: KeyList::with_capacity(kout),
+            am: KeyList::with_capacity(capacity),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Maximum number of resident entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of resident entries.
+    pub fn len(&self) -> usize {
+        self.a1in.len() + self.am.len()
+    }
+
+    /// Returns `true` if no entries are resident.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn reclaim(&mut self) {
+        if self.a1in.len() > self.kin || self.am.is_empty() {
+            if let Some(victim) = self.a1in.pop_back() {
+                self.a1out.push_front(victim);
+                if self.a1out.len() > self.kout {
+                    self.a1out.pop_back();
+                }
+                self.evicted.push(victim);
+            }
+        } else if let Some(victim) = self.am.pop_back() {
+            self.evicted.push(victim);
+        }
+    }
+}
+
+impl CacheModel for TwoQ {
+    fn get(&mut self, key: u64) -> bool {
+        self.am.move_to_front(key) || self.a1in.contains(key)
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.get(key) {
+            return;
+        }
+        // Take the key out of the ghost queue before reclaiming, which may
+        // push the ghost queue's tail out.
+        let ghost = self.a1out.remove(key);
+        if self.len() >= self.capacity {
+            self.reclaim();
+        }
+        if ghost {
+            self.am.push_front(key);
+        } else {
+            self.a1in.push_front(key);
+        }
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.am.contains(key) || self.a1in.contains(key)
+    }
+
+    fn delete(&mut self, key: u64) {
+        if !self.am.remove(key) && !self.a1in.remove(key) {
+            self.a1out.remove(key);
+        }
+    }
+
+    fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+        out.append(&mut self.evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_q_ghost_hit_promotes_to_main() {
+        let mut cache = TwoQ::with_ratios(4, 0.25, 1.0);
+        for key in 1..=5 {
+            cache.insert(key);
+        }
+        // Key 1 fell out of A1in into the ghost queue.
+        assert!(!cache.get(1));
+        cache.insert(1);
+        assert!(cache.am.contains(1));
+    }
+
+    #[test]
+    fn test_two_q_promotes_ghost_at_tail() {
+        // A one-entry ghost queue: reclaiming for key 1 pushes 2 into it,
+        // which must not push 1 out first.
+        let mut cache = TwoQ::with_ratios(4, 0.25, 0.25);
+        for key in 1..=5 {
+            cache.insert(key);
+        }
+        cache.insert(1);
+        assert!(cache.am.contains(1));
+    }
+
+    #[test]
+    fn test_two_q_scan_does_not_flush_main() {
+        let mut cache = TwoQ::new(4);
+        for key in 1..=5 {
+            cache.insert(key);
+        }
+        cache.insert(1);
+        cache.insert(2);
+        for key in 100..200 {
+            cache.insert(key);
+        }
+        assert!(cache.get(1));
+        assert!(cache.get(2));
+    }
+}
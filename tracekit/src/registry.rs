@@ -1,12 +1,18 @@
 //! Central registry for benchmark policies and workloads.
 //!
 //! This is the single source of truth for:
-//! - Policy definitions (id, display name, constructor)
+//! - Policy definitions (id, display name, parameters, constructor)
 //! - Workload definitions (id, display name, spec)
 //!
 //! To add a new policy or workload, modify this file only.
 //! All benchmarks and reports automatically pick up the changes.
 
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::CacheModel;
+use crate::policies::{ArcCache, Clock, Fifo, Lfu, Lirs, Lru, S3Fifo, Sieve, TinyLfu, TwoQ};
 use crate::workload::{Workload, WorkloadSpec};
 
 // ============================================================================
@@ -15,14 +21,389 @@ use crate::workload::{Workload, WorkloadSpec};
 
 // Note: The `for_each_policy!` macro for cachekit integration is provided by
 // the `tracekit-cachekit` crate. This keeps the core `tracekit` crate independent
-// of any specific cache implementation.
+// of any specific cache implementation. External crates can add their policies
+// to a `PolicyRegistry` at runtime instead.
+
+/// Boxed cache model produced by a [`PolicyRegistry`].
+pub type BoxedCacheModel = Box<dyn CacheModel + Send>;
+
+/// Constructor for a registered policy: `(capacity, params) -> model`.
+pub type PolicyFactory = fn(usize, &PolicyParams) -> BoxedCacheModel;
+
+/// Numeric parameter accepted by a registered policy.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamInfo {
+    /// Parameter name as written in specs (e.g., "kin").
+    pub name: &'static str,
+    /// Short description for help output.
+    pub description: &'static str,
+    /// Value used when the spec omits the parameter.
+    pub default: f64,
+}
+
+/// Policy entry with metadata and constructor.
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyEntry {
+    /// Short identifier (e.g., "lru", "s3_fifo").
+    pub id: &'static str,
+    /// Human-readable display name (e.g., "LRU", "S3-FIFO").
+    pub display_name: &'static str,
+    /// Accepted parameters.
+    pub params: &'static [ParamInfo],
+    /// Constructor.
+    pub factory: PolicyFactory,
+}
+
+/// Parameter values resolved for one policy instance.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyParams {
+    values: HashMap<&'static str, f64>,
+}
+
+impl PolicyParams {
+    /// Value of `name`; registered defaults are always filled in.
+    pub fn get(&self, name: &str) -> f64 {
+        self.values.get(name).copied().unwrap_or(0.0)
+    }
+}
+
+/// Parsed policy spec of the form `id[:key=value[,key=value]...]`.
+///
+/// Ids are case-insensitive and `-` is accepted for `_`, so `S3-FIFO` and
+/// `s3_fifo` name the same policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicySpec {
+    /// Normalized policy id.
+    pub id: String,
+    /// Parameters in the order given.
+    pub params: Vec<(String, f64)>,
+}
+
+impl fmt::Display for PolicySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)?;
+        for (idx, (name, value)) in self.params.iter().enumerate() {
+            let sep = if idx == 0 { ':' } else { ',' };
+            write!(f, "{sep}{name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PolicySpec {
+    type Err = RegistryError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (id, rest) = match spec.split_once(':') {
+            Some((id, rest)) => (id, Some(rest)),
+            None => (spec, None),
+        };
+        let id = id.trim().to_ascii_lowercase().replace('-', "_");
+        if id.is_empty() {
+            return Err(RegistryError::MalformedSpec(spec.to_string()));
+        }
+
+        let mut params: Vec<(String, f64)> = Vec::new();
+        for pair in rest.into_iter().flat_map(|r| r.split(',')) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| RegistryError::MalformedSpec(spec.to_string()))?;
+            let name = name.trim().to_ascii_lowercase();
+            let value = value.trim();
+            let parsed = value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| RegistryError::InvalidValue {
+                    policy: id.clone(),
+                    param: name.clone(),
+                    value: value.to_string(),
+                })?;
+            if params.iter().any(|(existing, _)| *existing == name) {
+                return Err(RegistryError::MalformedSpec(spec.to_string()));
+            }
+            params.push((name, parsed));
+        }
+
+        Ok(Self { id, params })
+    }
+}
+
+/// Errors from parsing or instantiating a policy spec.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    /// No policy is registered under this id.
+    UnknownPolicy(String),
+    /// The policy does not accept this parameter.
+    UnknownParameter { policy: String, param: String },
+    /// The parameter value is not a finite number.
+    InvalidValue {
+        policy: String,
+        param: String,
+        value: String,
+    },
+    /// The spec does not follow `id[:key=value,...]`.
+    MalformedSpec(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::UnknownPolicy(id) => write!(f, "unknown policy '{id}'"),
+            RegistryError::UnknownParameter { policy, param } => {
+                write!(f, "policy '{policy}' has no parameter '{param}'")
+            }
+            RegistryError::InvalidValue {
+                policy,
+                param,
+                value,
+            } => write!(
+                f,
+                "invalid value '{value}' for {policy} parameter '{param}'"
+            ),
+            RegistryError::MalformedSpec(spec) => {
+                write!(
+                    f,
+                    "malformed policy spec '{spec}' (expected id[:key=value,...])"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Runtime registry mapping policy ids to constructors.
+///
+/// [`PolicyRegistry::new`] contains the reference policies from
+/// [`policies`](crate::policies); other crates can [`register`](Self::register)
+/// their own.
+#[derive(Debug, Clone)]
+pub struct PolicyRegistry {
+    entries: Vec<PolicyEntry>,
+}
+
+impl Default for PolicyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PolicyRegistry {
+    /// Registry pre-populated with [`REFERENCE_POLICIES`].
+    pub fn new() -> Self {
+        Self {
+            entries: REFERENCE_POLICIES.to_vec(),
+        }
+    }
+
+    /// Registry with no policies.
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a policy, replacing any existing entry with the same id.
+    ///
+    /// # Panics
+    /// Panics if `entry.id` is not in normalized form (lowercase, `_` rather
+    /// than `-`), since [`get`](Self::get) could never find it.
+    pub fn register(&mut self, entry: PolicyEntry) {
+        assert!(
+            !entry.id.is_empty() && entry.id == entry.id.to_ascii_lowercase().replace('-', "_"),
+            "policy id {:?} is not normalized (expected e.g. \"s3_fifo\")",
+            entry.id
+        );
+        match self.entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Look up a policy by id (normalized like [`PolicySpec`] ids).
+    pub fn get(&self, id: &str) -> Option<&PolicyEntry> {
+        let id = id.to_ascii_lowercase().replace('-', "_");
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// All registered policies in registration order.
+    pub fn entries(&self) -> &[PolicyEntry] {
+        &self.entries
+    }
+
+    /// Registered policy ids in registration order.
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|e| e.id)
+    }
+
+    /// Check a spec against the registry and fill in default parameters.
+    pub fn resolve(
+        &self,
+        spec: &PolicySpec,
+    ) -> Result<(&PolicyEntry, PolicyParams), RegistryError> {
+        let entry = self
+            .get(&spec.id)
+            .ok_or_else(|| RegistryError::UnknownPolicy(spec.id.clone()))?;
+        let mut params = PolicyParams::default();
+        for info in entry.params {
+            params.values.insert(info.name, info.default);
+        }
+        for (name, value) in &spec.params {
+            let info = entry
+                .params
+                .iter()
+                .find(|p| p.name == name)
+                .ok_or_else(|| RegistryError::UnknownParameter {
+                    policy: entry.id.to_string(),
+                    param: name.clone(),
+                })?;
+            params.values.insert(info.name, *value);
+        }
+        Ok((entry, params))
+    }
+
+    /// Instantiate a parsed spec at `capacity`.
+    pub fn build_spec(
+        &self,
+        spec: &PolicySpec,
+        capacity: usize,
+    ) -> Result<BoxedCacheModel, RegistryError> {
+        let (entry, params) = self.resolve(spec)?;
+        Ok((entry.factory)(capacity, &params))
+    }
+
+    /// Display name for `spec`, e.g. "2Q (kin=0.25)" when parameters are set.
+    pub fn display_name(&self, spec: &PolicySpec) -> Result<String, RegistryError> {
+        let (entry, _) = self.resolve(spec)?;
+        if spec.params.is_empty() {
+            return Ok(entry.display_name.to_string());
+        }
+        let params: Vec<String> = spec
+            .params
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        Ok(format!("{} ({})", entry.display_name, params.join(",")))
+    }
+
+    /// Parse and instantiate a spec string such as `"2q:kin=0.25"`.
+    pub fn build(&self, spec: &str, capacity: usize) -> Result<BoxedCacheModel, RegistryError> {
+        self.build_spec(&spec.parse()?, capacity)
+    }
+}
+
+/// Reference policies from [`policies`](crate::policies).
+pub const REFERENCE_POLICIES: &[PolicyEntry] = &[
+    PolicyEntry {
+        id: "lru",
+        display_name: "LRU",
+        params: &[],
+        factory: |capacity, _| Box::new(Lru::new(capacity)),
+    },
+    PolicyEntry {
+        id: "fifo",
+        display_name: "FIFO",
+        params: &[],
+        factory: |capacity, _| Box::new(Fifo::new(capacity)),
+    },
+    PolicyEntry {
+        id: "clock",
+        display_name: "Clock",
+        params: &[],
+        factory: |capacity, _| Box::new(Clock::new(capacity)),
+    },
+    PolicyEntry {
+        id: "lfu",
+        display_name: "LFU",
+        params: &[],
+        factory: |capacity, _| Box::new(Lfu::new(capacity)),
+    },
+    PolicyEntry {
+        id: "2q",
+        display_name: "2Q",
+        params: &[
+            ParamInfo {
+                name: "kin",
+                description: "A1in size as a fraction of capacity",
+                default: crate::policies::two_q::DEFAULT_KIN,
+            },
+            ParamInfo {
+                name: "kout",
+                description: "A1out ghost size as a fraction of capacity",
+                default: crate::policies::two_q::DEFAULT_KOUT,
+            },
+        ],
+        factory: |capacity, params| {
+            Box::new(TwoQ::with_ratios(
+                capacity,
+                params.get("kin"),
+                params.get("kout"),
+            ))
+        },
+    },
+    PolicyEntry {
+        id: "arc",
+        display_name: "ARC",
+        params: &[],
+        factory: |capacity, _| Box::new(ArcCache::new(capacity)),
+    },
+    PolicyEntry {
+        id: "lirs",
+        display_name: "LIRS",
+        params: &[ParamInfo {
+            name: "hir_ratio",
+            description: "resident HIR share of capacity",
+            default: crate::policies::lirs::DEFAULT_HIR_RATIO,
+        }],
+        factory: |capacity, params| {
+            Box::new(Lirs::with_hir_ratio(capacity, params.get("hir_ratio")))
+        },
+    },
+    PolicyEntry {
+        id: "s3_fifo",
+        display_name: "S3-FIFO",
+        params: &[ParamInfo {
+            name: "small_ratio",
+            description: "small queue share of capacity",
+            default: crate::policies::s3_fifo::DEFAULT_SMALL_RATIO,
+        }],
+        factory: |capacity, params| {
+            Box::new(S3Fifo::with_small_ratio(
+                capacity,
+                params.get("small_ratio"),
+            ))
+        },
+    },
+    PolicyEntry {
+        id: "sieve",
+        display_name: "SIEVE",
+        params: &[],
+        factory: |capacity, _| Box::new(Sieve::new(capacity)),
+    },
+    PolicyEntry {
+        id: "tiny_lfu",
+        display_name: "W-TinyLFU",
+        params: &[ParamInfo {
+            name: "window_ratio",
+            description: "admission window share of capacity",
+            default: crate::policies::tiny_lfu::DEFAULT_WINDOW_RATIO,
+        }],
+        factory: |capacity, params| {
+            Box::new(TinyLfu::with_window_ratio(
+                capacity,
+                params.get("window_ratio"),
+            ))
+        },
+    },
+];
 
 // ============================================================================
 // Workload Registry
 // ============================================================================
 
 /// Workload case with metadata.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct WorkloadCase {
     /// Short identifier (e.g., "uniform", "zipfian_1.0").
     pub id: &'static str,
@@ -206,17 +587,83 @@ pub const EXTENDED_WORKLOADS: &[WorkloadCase] = &[
     WorkloadCase {
         id: "mixture",
         display_name: "Mixture",
-        workload: Workload::Mixture,
+        workload: Workload::MIXTURE,
     },
 ];
 
 /// Build a `WorkloadSpec` from a workload case and runtime parameters.
 impl WorkloadCase {
-    pub fn with_params(self, universe: u64, seed: u64) -> WorkloadSpec {
-        WorkloadSpec {
-            universe,
-            workload: self.workload,
-            seed,
+    pub fn with_params(&self, universe: u64, seed: u64) -> WorkloadSpec {
+        WorkloadSpec::new(universe, self.workload.clone(), seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_spec_parsing() {
+        let spec: PolicySpec = "2Q:kin=0.3, kout=0.6".parse().unwrap();
+        assert_eq!(spec.id, "2q");
+        assert_eq!(spec.params, vec![("kin".into(), 0.3), ("kout".into(), 0.6)]);
+        assert_eq!(spec.to_string(), "2q:kin=0.3,kout=0.6");
+
+        assert_eq!("S3-FIFO".parse::<PolicySpec>().unwrap().id, "s3_fifo");
+        assert!(matches!(
+            "lru:oops".parse::<PolicySpec>(),
+            Err(RegistryError::MalformedSpec(_))
+        ));
+        assert!(matches!(
+            "2q:kin=abc".parse::<PolicySpec>(),
+            Err(RegistryError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_registry_builds_every_reference_policy() {
+        let registry = PolicyRegistry::new();
+        for id in registry.ids() {
+            let mut cache = registry.build(id, 10).unwrap();
+            cache.insert(1);
+            assert!(cache.get(1), "{id} lost a freshly inserted key");
         }
+        // Ids are snake_case like the policy modules.
+        assert!(registry.build("tiny_lfu", 10).is_ok());
+        assert!(registry.build("tiny-lfu:window_ratio=0.1", 10).is_ok());
+    }
+
+    #[test]
+    fn test_registry_rejects_unknown_names() {
+        let registry = PolicyRegistry::new();
+        assert_eq!(
+            registry.build("nope", 10).err(),
+            Some(RegistryError::UnknownPolicy("nope".into()))
+        );
+        assert!(matches!(
+            registry.build("lru:kin=0.5", 10),
+            Err(RegistryError::UnknownParameter { .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "not normalized")]
+    fn test_register_rejects_unnormalized_ids() {
+        let mut registry = PolicyRegistry::new();
+        let entry = *registry.get("s3_fifo").unwrap();
+        registry.register(PolicyEntry {
+            id: "S3-FIFO",
+            ..entry
+        });
+    }
+
+    #[test]
+    fn test_registry_defaults_and_overrides() {
+        let registry = PolicyRegistry::new();
+        let spec: PolicySpec = "2q:kout=0.75".parse().unwrap();
+        let (entry, params) = registry.resolve(&spec).unwrap();
+        assert_eq!(entry.display_name, "2Q");
+        assert_eq!(params.get("kin"), crate::policies::two_q::DEFAULT_KIN);
+        assert_eq!(params.get("kout"), 0.75);
     }
 }
@@ -0,0 +1,430 @@
+//! Phased workloads that change over time.
+//!
+//! ## Architecture
+//! A [`WorkloadSchedule`] is a sequence of [`Phase`]s, each running one
+//! [`Workload`] for a fixed number of events. [`ScheduleGenerator`] replays
+//! the phases in order through a single [`WorkloadGenerator`], switching its
+//! key distribution at phase boundaries, so the random stream and positional
+//! state (scan position, insert counter, arrival clock) carry over from one
+//! phase to the next. The schedule's operation mix, object sizes, arrivals
+//! and TTLs apply to every phase, exactly as in a [`WorkloadSpec`].
+//!
+//! ## Interpolation
+//! A phase with `interpolate` set moves its parameters linearly towards
+//! those of the next phase over its duration (see
+//! [`Workload::interpolate`]), e.g. a Zipf exponent drifting from 0.6 to 1.2.
+//! Parameters are updated in [`INTERPOLATION_STEPS`] steps per phase.
+//! Interpolation only applies between phases of the same kind (mixtures
+//! only between identical ones); [`WorkloadSchedule::validate`] rejects
+//! anything else. The last phase never interpolates.
+//!
+//! ## Spec Files
+//! Schedules are (de)serializable, so they can be written as JSON:
+//! ```json
+//! {
+//!   "universe": 100000,
+//!   "seed": 7,
+//!   "ops": {"read": 0.9, "update": 0.1},
+//!   "phases": [
+//!     {"workload": {"kind": "zipfian", "exponent": 0.6}, "operations": 500000, "interpolate": true},
+//!     {"workload": {"kind": "zipfian", "exponent": 1.2}, "operations": 200000},
+//!     {"workload": {"kind": "scan"}, "operations": 50000}
+//!   ]
+//! }
+//! ```
+//! `ops`, `sizes`, `arrivals` and `ttl` are optional and use the same
+//! encoding as the corresponding [`WorkloadSpec`] fields.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::arrival::{ArrivalModel, TtlModel};
+use crate::event::Event;
+use crate::op_mix::OpMix;
+use crate::sizes::SizeModel;
+use crate::source::EventSource;
+use crate::workload::{Workload, WorkloadGenerator, WorkloadSpec};
+
+/// Parameter updates per interpolated phase.
+pub const INTERPOLATION_STEPS: u64 = 1024;
+
+/// One phase of a [`WorkloadSchedule`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Phase {
+    /// Workload generated during the phase.
+    pub workload: Workload,
+    /// Length of the phase, in events.
+    pub operations: u64,
+    /// Move parameters towards the next phase's over the phase.
+    #[serde(default)]
+    pub interpolate: bool,
+}
+
+impl Phase {
+    /// A phase with fixed parameters.
+    pub fn new(workload: Workload, operations: u64) -> Self {
+        Self {
+            workload,
+            operations,
+            interpolate: false,
+        }
+    }
+
+    /// A phase whose parameters move towards the next phase's.
+    pub fn interpolated(workload: Workload, operations: u64) -> Self {
+        Self {
+            interpolate: true,
+            ..Self::new(workload, operations)
+        }
+    }
+}
+
+/// Sequence of workload phases over one key universe.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadSchedule {
+    /// Key universe shared by all phases.
+    pub universe: u64,
+    /// Random seed for the whole schedule.
+    pub seed: u64,
+    /// Operations emitted in every phase; read-only by default.
+    #[serde(default, skip_serializing_if = "OpMix::is_read_only")]
+    pub ops: OpMix,
+    /// Object sizes set as `Event::weight`; `None` leaves events unweighted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sizes: Option<SizeModel>,
+    /// Arrival times set as `Event::ts`; `None` leaves events untimed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrivals: Option<ArrivalModel>,
+    /// Per-key TTLs set as `Event::ttl`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<TtlModel>,
+    /// Phases, in order.
+    pub phases: Vec<Phase>,
+}
+
+impl WorkloadSchedule {
+    /// A read-only schedule of `phases`.
+    pub fn new(universe: u64, seed: u64, phases: Vec<Phase>) -> Self {
+        Self {
+            universe,
+            seed,
+            ops: OpMix::READ_ONLY,
+            sizes: None,
+            arrivals: None,
+            ttl: None,
+            phases,
+        }
+    }
+
+    /// Total number of events over all phases.
+    pub fn operations(&self) -> u64 {
+        self.phases.iter().map(|phase| phase.operations).sum()
+    }
+
+    /// The schedule's settings applied to `workload`.
+    pub fn spec(&self, workload: Workload) -> WorkloadSpec {
+        let mut spec = WorkloadSpec::new(self.universe, workload, self.seed).with_ops(self.ops);
+        spec.sizes = self.sizes.clone();
+        spec.arrivals = self.arrivals;
+        spec.ttl = self.ttl;
+        spec
+    }
+
+    /// Check that every interpolated phase can reach the next phase's
+    /// parameters.
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        let phases: Vec<(usize, &Phase)> = self
+            .phases
+            .iter()
+            .enumerate()
+            .filter(|(_, phase)| phase.operations > 0)
+            .collect();
+        for pair in phases.windows(2) {
+            let ((index, phase), (_, next)) = (pair[0], pair[1]);
+            if phase.interpolate && phase.workload.interpolate(&next.workload, 0.0).is_none() {
+                return Err(ScheduleError::IncompatibleInterpolation {
+                    phase: index,
+                    from: phase.workload.name(),
+                    to: next.workload.name(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Bounded event source replaying the schedule.
+    ///
+    /// # Panics
+    /// Panics if the schedule fails [`validate`](Self::validate).
+    pub fn generator(&self) -> ScheduleGenerator {
+        ScheduleGenerator::new(self)
+    }
+}
+
+/// Error from [`WorkloadSchedule::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// An interpolated phase is followed by a workload it cannot reach.
+    IncompatibleInterpolation {
+        /// Index of the interpolated phase in `phases`.
+        phase: usize,
+        /// Workload kind of the phase.
+        from: &'static str,
+        /// Workload kind of the next non-empty phase.
+        to: &'static str,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::IncompatibleInterpolation { phase, from, to } => write!(
+                f,
+                "phase {phase} cannot interpolate from {from} to {to} \
+                 (only workloads of one kind, or identical mixtures, interpolate)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// Event source for a [`WorkloadSchedule`].
+#[derive(Debug, Clone)]
+pub struct ScheduleGenerator {
+    phases: Vec<Phase>,
+    generator: WorkloadGenerator,
+    /// Index of the current phase.
+    phase: usize,
+    /// Events emitted in the current phase.
+    position: u64,
+    remaining: u64,
+}
+
+impl ScheduleGenerator {
+    /// Start replaying `schedule` from its first phase.
+    ///
+    /// # Panics
+    /// Panics if the schedule fails [`WorkloadSchedule::validate`].
+    pub fn new(schedule: &WorkloadSchedule) -> Self {
+        if let Err(err) = schedule.validate() {
+            panic!("{err}");
+        }
+        // Empty phases would never emit an event; skipping them keeps the
+        // phase index in step with the output.
+        let phases: Vec<Phase> = schedule
+            .phases
+            .iter()
+            .filter(|phase| phase.operations > 0)
+            .cloned()
+            .collect();
+        let first = phases
+            .first()
+            .map_or(Workload::Uniform, |p| p.workload.clone());
+        Self {
+            generator: schedule.spec(first).generator(),
+            remaining: phases.iter().map(|phase| phase.operations).sum(),
+            phases,
+            phase: 0,
+            position: 0,
+        }
+    }
+
+    /// Index of the phase the next event belongs to.
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+
+    /// Workload parameters currently in effect.
+    pub fn workload(&self) -> &Workload {
+        self.generator.workload()
+    }
+
+    /// Next key, or `None` once every phase has run.
+    pub fn next_key(&mut self) -> Option<u64> {
+        self.advance().then(|| self.generator.next_key())
+    }
+
+    /// Move to the next event's phase and parameters, returning `false` once
+    /// every phase has run.
+    fn advance(&mut self) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        if self.position == self.phases[self.phase].operations {
+            self.phase += 1;
+            self.position = 0;
+            self.generator
+                .set_workload(self.phases[self.phase].workload.clone());
+        }
+        let phase = &self.phases[self.phase];
+        if phase.interpolate {
+            if let Some(next) = self.phases.get(self.phase + 1) {
+                let step = (phase.operations / INTERPOLATION_STEPS).max(1);
+                if self.position % step == 0 {
+                    let t = self.position as f64 / phase.operations as f64;
+                    // `validate` guarantees the phases interpolate.
+                    let workload = phase.workload.interpolate(&next.workload, t).unwrap();
+                    self.generator.set_workload(workload);
+                }
+            }
+        }
+        self.position += 1;
+        self.remaining -= 1;
+        true
+    }
+}
+
+/// Events follow the schedule's operation mix, sizes, arrivals and TTLs.
+impl EventSource for ScheduleGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        if self.advance() {
+            self.generator.next_event()
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_run_in_order() {
+        let schedule = WorkloadSchedule::new(
+            100,
+            1,
+            vec![
+                Phase::new(
+                    Workload::Loop {
+                        working_set_size: 3,
+                    },
+                    5,
+                ),
+                Phase::new(Workload::Uniform, 0),
+                Phase::new(Workload::Scan, 4),
+            ],
+        );
+        let mut source = schedule.generator();
+        assert_eq!(source.size_hint(), Some(9));
+
+        let keys: Vec<u64> = std::iter::from_fn(|| source.next_key()).collect();
+        // The scan picks up the generator's fresh scan position.
+        assert_eq!(keys, vec![0, 1, 2, 0, 1, 0, 1, 2, 3]);
+        assert_eq!(source.phase(), 1);
+    }
+
+    #[test]
+    fn test_interpolation_reaches_next_phase() {
+        let schedule = WorkloadSchedule::new(
+            1_000,
+            1,
+            vec![
+                Phase::interpolated(Workload::Zipfian { exponent: 0.5 }, 2_048),
+                Phase::new(Workload::Zipfian { exponent: 1.5 }, 10),
+            ],
+        );
+        let mut source = schedule.generator();
+        for _ in 0..1_025 {
+            source.next_key();
+        }
+        assert_eq!(*source.workload(), Workload::Zipfian { exponent: 1.0 });
+        for _ in 0..1_024 {
+            source.next_key();
+        }
+        assert_eq!(*source.workload(), Workload::Zipfian { exponent: 1.5 });
+    }
+
+    #[test]
+    fn test_interpolation_across_kinds_is_rejected() {
+        let mut schedule = WorkloadSchedule::new(
+            100,
+            1,
+            vec![
+                Phase::interpolated(Workload::Zipfian { exponent: 0.5 }, 10),
+                Phase::new(Workload::Uniform, 0),
+                Phase::new(Workload::Scan, 10),
+            ],
+        );
+        assert_eq!(
+            schedule.validate(),
+            Err(ScheduleError::IncompatibleInterpolation {
+                phase: 0,
+                from: "Zipfian",
+                to: "Scan",
+            })
+        );
+        // Identical mixtures interpolate trivially; anything else does not.
+        schedule.phases = vec![
+            Phase::interpolated(Workload::MIXTURE, 10),
+            Phase::new(Workload::MIXTURE, 10),
+        ];
+        assert!(schedule.validate().is_ok());
+        schedule.phases[1] = Phase::new(
+            Workload::Mixture {
+                components: vec![crate::MixtureComponent::new(1.0, Workload::Scan)].into(),
+            },
+            10,
+        );
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn test_events_follow_schedule_settings() {
+        let json = r#"{
+            "universe": 50,
+            "seed": 2,
+            "ops": {"read": 1.0, "insert": 1.0},
+            "sizes": {"distribution": {"kind": "constant", "size": 64}},
+            "arrivals": {"process": {"kind": "constant", "rate": 1.0}},
+            "ttl": {"kind": "fixed", "ttl": 30},
+            "phases": [
+                {"workload": {"kind": "uniform"}, "operations": 100},
+                {"workload": {"kind": "scan"}, "operations": 100}
+            ]
+        }"#;
+        let schedule: WorkloadSchedule = serde_json::from_str(json).unwrap();
+        let mut source = schedule.generator();
+        let events: Vec<Event> = std::iter::from_fn(|| source.next_event()).collect();
+
+        assert_eq!(events.len(), 200);
+        assert!(
+            events
+                .iter()
+                .any(|e| e.op == crate::Op::Insert && e.key > 50)
+        );
+        assert!(
+            events
+                .iter()
+                .all(|e| e.weight == Some(64) && e.ttl == Some(30))
+        );
+        assert_eq!(events.last().unwrap().ts, Some(200));
+    }
+
+    #[test]
+    fn test_schedule_round_trips_through_json() {
+        let json = r#"{
+            "universe": 10,
+            "seed": 3,
+            "phases": [
+                {"workload": {"kind": "hot_set", "hot_fraction": 0.1, "hot_prob": 0.9},
+                 "operations": 5, "interpolate": true},
+                {"workload": {"kind": "scan"}, "operations": 5}
+            ]
+        }"#;
+        let schedule: WorkloadSchedule = serde_json::from_str(json).unwrap();
+        assert_eq!(schedule.operations(), 10);
+        assert!(schedule.phases[0].interpolate && !schedule.phases[1].interpolate);
+        let encoded = serde_json::to_string(&schedule).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WorkloadSchedule>(&encoded).unwrap(),
+            schedule
+        );
+    }
+}
@@ -6,78 +6,1178 @@
 //!
 //! ## Core Operations
 //! - Get: Check cache, record hit/miss, insert on miss
-//! - Insert: Direct insertion
+//! - Insert: Direct insertion; with [`ReplayMode::count_updates`], an insert
+//!   of a resident key counts as an update
 //! - Delete: Remove from cache
+//!
+//! ## Write Policies
+//! [`ReplayMode::writes`] selects what [`simulate_observed`] and the
+//! `simulate_many*` drivers do with Inserts:
+//! - [`WritePolicy::AllocateOnMiss`] (default): update resident keys and
+//!   allocate missing ones
+//! - [`WritePolicy::Through`]: update resident keys; missing keys go to the
+//!   backing store only
+//! - [`WritePolicy::Around`]: bypass the cache and invalidate a resident copy
+//!
+//! Residency is checked with [`CacheModel::contains`], which models must
+//! implement for [`WritePolicy::Through`] and [`ReplayMode::count_updates`].
+//!
+//! ## Miss Classification
+//! With [`ReplayMode::classify_misses`] set, drivers classify misses as
+//! compulsory (first reference to a key), delete-caused (the key was deleted,
+//! or expired, since its last reference) or capacity (everything else) in the
+//! `*_misses` fields of `HitStats`. Classification remembers every distinct
+//! key, so it is off by default and `Outcome::Miss` then carries no kind.
+//!
+//! ## Observers
+//! [`simulate_observed`] is the generic driver: it replays the trace once and
+//! hands every event, its [`Outcome`] and any evictions to a
+//! [`SimObserver`]. [`simulate`], [`simulate_explicit`],
+//! [`simulate_with_evictions`], [`simulate_weighted`], [`simulate_timed`] and
+//! [`simulate_windowed`] are built on it.
+//!
+//! ## Warmup
+//! [`simulate_warm`] replays a leading [`Warmup`] into the cache without
+//! counting it. Any observer-based driver can do the same by wrapping its
+//! observer in a [`WarmupFilter`].
+//!
+//! ## Multi-Model Simulation
+//! [`simulate_many`] reads a trace once and replays it through a slice of
+//! models, so comparing policies costs one parse instead of one per policy.
+//! [`simulate_many_threaded`] and [`simulate_many_observed`] spread the models
+//! over worker threads fed with bounded batches.
+//!
+//! ## Weighted Simulation
+//! [`ReplayMode::weighted`] honors [`Event::weight`](crate::Event::weight)
+//! and reports byte hit ratio alongside object hit ratio. Events without a
+//! weight count as one unit. [`simulate_weighted`] is [`simulate`] with it set.
+//!
+//! ## Time-Aware Simulation
+//! [`Expiry::Ttl`] advances a logical clock from
+//! [`Event::ts`](crate::Event::ts) and expires entries as soon as the clock
+//! passes their deadline, from a min-heap of deadlines. Expirations are
+//! counted separately from capacity evictions and reported through
+//! [`SimObserver::on_expire`]. [`simulate_timed`] is [`simulate`] with it set.
+//!
+//! ## Windowed Simulation
+//! [`simulate_windowed`] additionally reports per-window statistics through a
+//! [`WindowedStats`] recorder, for hit rate over the course of a trace.
+//!
+//! ## Eviction Accounting
+//! Every driver drains [`CacheModel::drain_evictions`] after each insert and
+//! counts the reported keys in `HitStats::evictions`.
+//! [`simulate_with_evictions`] additionally hands each evicted key and the
+//! index of the event that caused it to a callback.
 
-use crate::event::Op;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, mpsc};
+
+use crate::event::{Event, Op};
 use crate::metrics::HitStats;
 use crate::model::CacheModel;
+use crate::observer::{MissClassifier, MissKind, OnEvict, Outcome, SimObserver, WarmupFilter};
 use crate::source::EventSource;
+use crate::timeseries::{WindowStats, WindowedStats};
+
+/// What the driver does when a Get misses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnMiss {
+    /// Insert the missed key (read-through).
+    #[default]
+    Insert,
+    /// Leave the cache untouched; the trace supplies explicit Inserts.
+    Skip,
+}
+
+/// What the driver does with an explicit write (`Op::Insert`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WritePolicy {
+    /// Write-allocate: update a resident key or insert a missing one.
+    #[default]
+    AllocateOnMiss,
+    /// Write-through without allocation: update a resident key, leave the
+    /// cache untouched otherwise.
+    Through,
+    /// Write-around: never store the write; a resident copy is deleted so
+    /// the next read fetches the new value.
+    Around,
+}
+
+/// Whether a driver keeps time and expires entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Expiry {
+    /// Ignore `Event::ts` and `Event::ttl`; entries never expire.
+    #[default]
+    Never,
+    /// Advance a logical clock from `Event::ts` (events without a timestamp
+    /// keep the current time), notify the model via
+    /// [`CacheModel::advance_time`] and expire entries whose TTL has elapsed.
+    /// Every insert arms a deadline from the event's `ttl`, falling back to
+    /// `default_ttl`; `None` for both means the entry never expires. Entries
+    /// are deleted from the model before the first event at or past their
+    /// deadline, whatever its key or operation.
+    Ttl { default_ttl: Option<u64> },
+}
+
+/// How a driver applies the trace to the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayMode {
+    /// Handling of Get misses.
+    pub on_miss: OnMiss,
+    /// Handling of explicit writes.
+    pub writes: WritePolicy,
+    /// Insert through [`CacheModel::insert_weighted`] with each event's
+    /// weight (default 1) and count `hit_bytes`/`miss_bytes`.
+    pub weighted: bool,
+    /// Clock and TTL handling.
+    pub expiry: Expiry,
+    /// Classify misses by [`MissKind`]. Costs one map entry per distinct key.
+    pub classify_misses: bool,
+    /// Count inserts of resident keys as updates, and expire only resident
+    /// keys. Needs a model that implements [`CacheModel::contains`].
+    pub count_updates: bool,
+}
+
+impl ReplayMode {
+    /// Whether the driver asks the model for residency.
+    fn checks_residency(&self) -> bool {
+        self.count_updates || self.writes != WritePolicy::AllocateOnMiss
+    }
+}
+
+impl From<OnMiss> for ReplayMode {
+    fn from(on_miss: OnMiss) -> Self {
+        Self {
+            on_miss,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<WritePolicy> for ReplayMode {
+    fn from(writes: WritePolicy) -> Self {
+        Self {
+            writes,
+            ..Self::default()
+        }
+    }
+}
+
+/// Leading part of a replay that only fills the cache.
+///
+/// Warmup events are replayed into the model but excluded from statistics,
+/// so cold-start misses do not distort comparisons on short traces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Warmup {
+    /// The first N events.
+    Events(u64),
+    /// A fraction of the trace, in `[0, 1]`.
+    Fraction(f64),
+    /// Every event up to and including the one whose insert causes the first
+    /// eviction. Models that do not report evictions never finish warming up.
+    UntilFull,
+}
+
+impl Warmup {
+    /// Number of warmup events for a trace of `trace_len` events, or `None`
+    /// for [`Warmup::UntilFull`].
+    pub fn events(self, trace_len: u64) -> Option<u64> {
+        match self {
+            Warmup::Events(n) => Some(n),
+            Warmup::Fraction(fraction) => {
+                Some((trace_len as f64 * fraction.clamp(0.0, 1.0)).round() as u64)
+            }
+            Warmup::UntilFull => None,
+        }
+    }
+}
 
 /// Run a trace simulation, returning hit statistics.
 ///
 /// On a cache miss during a Get operation, the key is automatically inserted.
 /// This models the common "read-through" cache pattern.
 pub fn simulate<C, S>(cache: &mut C, source: &mut S) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+{
+    simulate_observed(cache, source, OnMiss::Insert, &mut ())
+}
+
+/// Run a trace simulation, reporting every eviction to `on_evict`.
+///
+/// Same semantics as [`simulate`]. `on_evict` receives the evicted key and the
+/// zero-based index of the event whose insert displaced it, which allows
+/// downstream analysis of which keys were thrown out and when.
+pub fn simulate_with_evictions<C, S, F>(cache: &mut C, source: &mut S, on_evict: F) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+    F: FnMut(u64, u64),
+{
+    simulate_observed(cache, source, OnMiss::Insert, &mut OnEvict(on_evict))
+}
+
+/// Run a simulation without auto-insert on miss.
+///
+/// Use this when the trace explicitly contains Insert events and you don't
+/// want automatic insertion on cache misses.
+pub fn simulate_explicit<C, S>(cache: &mut C, source: &mut S) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+{
+    simulate_observed(cache, source, OnMiss::Skip, &mut ())
+}
+
+/// Run a trace simulation, excluding `warmup` from the statistics.
+///
+/// Same semantics as [`simulate`]. [`Warmup::Fraction`] needs the trace
+/// length: it is taken from [`EventSource::size_hint`] when available,
+/// otherwise the trace is buffered in memory first.
+pub fn simulate_warm<C, S>(cache: &mut C, source: &mut S, warmup: Warmup) -> HitStats
 where
     C: CacheModel,
     S: EventSource,
 {
     let mut stats = HitStats::default();
+    match (warmup, source.size_hint()) {
+        (Warmup::Fraction(_), None) => {
+            let events: Vec<Event> = std::iter::from_fn(|| source.next_event()).collect();
+            let mut filter = WarmupFilter::new(warmup, Some(events.len() as u64), &mut stats);
+            simulate_observed(cache, &mut events.into_iter(), OnMiss::Insert, &mut filter);
+        }
+        (_, len) => {
+            let mut filter = WarmupFilter::new(warmup, len.map(|n| n as u64), &mut stats);
+            simulate_observed(cache, source, OnMiss::Insert, &mut filter);
+        }
+    }
+    stats
+}
+
+/// Run a simulation, reporting hit statistics per window.
+///
+/// Same semantics as [`simulate`]. Each event is assigned to a window by
+/// `windows` and every window that closes during the call is passed to
+/// `on_window`. The recorder keeps its state between calls, so a trace can be
+/// replayed in batches; call [`WindowedStats::finish`] afterwards to flush the
+/// last window. Returns the totals for the events replayed by this call.
+pub fn simulate_windowed<C, S, F>(
+    cache: &mut C,
+    source: &mut S,
+    windows: &mut WindowedStats,
+    on_window: F,
+) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+    F: FnMut(WindowStats),
+{
+    let events = std::iter::from_fn(|| source.next_event()).map(|e| (e, None));
+    Replay::new(ReplayMode::default()).run(cache, events, &mut windows.observer(on_window), 0)
+}
+
+/// Run a simulation, passing every event and its outcome to `observer`.
+///
+/// This is the generic driver behind [`simulate`], [`simulate_explicit`],
+/// [`simulate_with_evictions`], [`simulate_weighted`], [`simulate_timed`] and
+/// [`simulate_windowed`]. Evictions are drained after each insert and
+/// reported to the observer with the index of the event that caused them.
+/// Returns the totals for the replay.
+///
+/// `mode` is a [`ReplayMode`], or just an [`OnMiss`] or [`WritePolicy`] with
+/// everything else left at its default.
+pub fn simulate_observed<C, S, O>(
+    cache: &mut C,
+    source: &mut S,
+    mode: impl Into<ReplayMode>,
+    observer: &mut O,
+) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+    O: SimObserver + ?Sized,
+{
+    let mode = mode.into();
+    let mut classifier = mode.classify_misses.then(MissClassifier::default);
+    let events =
+        std::iter::from_fn(|| source.next_event()).map(|e| classified(&mut classifier, e, mode));
+    Replay::new(mode).run(cache, events, observer, 0)
+}
+
+/// Per-model replay state, carried across the batches of one trace.
+#[derive(Debug)]
+pub(crate) struct Replay {
+    mode: ReplayMode,
+    /// Logical clock under [`Expiry::Ttl`].
+    now: u64,
+    /// Current expiry deadline of every entry inserted with a TTL.
+    deadlines: HashMap<u64, u64>,
+    /// Min-heap of `(deadline, key)`. Entries whose deadline no longer
+    /// matches `deadlines` are stale and skipped when popped.
+    expiring: BinaryHeap<Reverse<(u64, u64)>>,
+    /// Keys expired since their last reference, when classifying misses.
+    expired: HashSet<u64>,
+    /// Evictions are drained after the inserting event when this is 1, or
+    /// every `drain_every` events (and at the end of a run) otherwise.
+    drain_every: u64,
+    evicted: Vec<u64>,
+}
+
+impl Replay {
+    pub(crate) fn new(mode: ReplayMode) -> Self {
+        Self {
+            mode,
+            now: 0,
+            deadlines: HashMap::new(),
+            expiring: BinaryHeap::new(),
+            expired: HashSet::new(),
+            drain_every: 1,
+            evicted: Vec::new(),
+        }
+    }
 
-    while let Some(event) = source.next_event() {
-        match event.op {
-            Op::Get => {
-                if cache.get(event.key) {
-                    stats.hits += 1;
-                } else {
-                    stats.misses += 1;
-                    cache.insert(event.key);
-                    stats.inserts += 1;
+    /// Drain evictions every `events` events instead of after each insert.
+    ///
+    /// Evicted keys are then reported with the index of the event that
+    /// triggered the drain, not the one that caused them.
+    pub(crate) fn drain_every(mut self, events: u64) -> Self {
+        self.drain_every = events.max(1);
+        self
+    }
+
+    /// Replay loop of every driver, numbering events from `first_index`.
+    ///
+    /// Each event comes paired with the kind a miss on it would have, if
+    /// misses are classified. That only depends on the trace, so batched
+    /// callers keep one [`MissClassifier`] across batches and share its
+    /// output between models.
+    /// Returns the totals for `events`.
+    pub(crate) fn run<C, I, O>(
+        &mut self,
+        cache: &mut C,
+        events: I,
+        observer: &mut O,
+        first_index: u64,
+    ) -> HitStats
+    where
+        C: CacheModel + ?Sized,
+        I: IntoIterator<Item = (Event, Option<MissKind>)>,
+        O: SimObserver + ?Sized,
+    {
+        let mut stats = HitStats::default();
+        let timed = self.mode.expiry != Expiry::Never;
+        let mut undrained = false;
+        let mut last_index = None;
+
+        for (index, (event, mut kind)) in (first_index..).zip(events) {
+            let ttl = match self.mode.expiry {
+                Expiry::Never => None,
+                Expiry::Ttl { default_ttl } => {
+                    self.expire(cache, &event, index, &mut stats, observer);
+                    if event.op == Op::Get && self.expired.remove(&event.key) {
+                        kind = kind.map(|kind| match kind {
+                            MissKind::Capacity => MissKind::Delete,
+                            kind => kind,
+                        });
+                    }
+                    event.ttl.or(default_ttl)
+                }
+            };
+
+            let outcome = match event.op {
+                Op::Get => {
+                    if cache.get(event.key) {
+                        Outcome::Hit
+                    } else {
+                        let inserted = self.mode.on_miss == OnMiss::Insert;
+                        if inserted {
+                            self.fill(cache, &event);
+                        }
+                        Outcome::Miss { inserted, kind }
+                    }
+                }
+                Op::Insert => {
+                    let resident = self.mode.checks_residency() && cache.contains(event.key);
+                    match (resident, self.mode.writes) {
+                        (true, WritePolicy::AllocateOnMiss | WritePolicy::Through) => {
+                            self.fill(cache, &event);
+                            Outcome::Update
+                        }
+                        (false, WritePolicy::AllocateOnMiss) => {
+                            self.fill(cache, &event);
+                            Outcome::Insert
+                        }
+                        (false, WritePolicy::Through) => Outcome::Bypass,
+                        (_, WritePolicy::Around) => {
+                            cache.delete(event.key);
+                            Outcome::Bypass
+                        }
+                    }
+                }
+                Op::Delete => {
+                    cache.delete(event.key);
+                    Outcome::Delete
+                }
+            };
+            if self.mode.weighted {
+                let weight = event.weight.unwrap_or(1) as u64;
+                match outcome {
+                    Outcome::Hit => stats.hit_bytes += weight,
+                    Outcome::Miss { .. } => stats.miss_bytes += weight,
+                    _ => {}
                 }
             }
-            Op::Insert => {
-                cache.insert(event.key);
-                stats.inserts += 1;
+            if timed {
+                if outcome.inserted() {
+                    self.arm(event.key, ttl);
+                    self.expired.remove(&event.key);
+                } else if event.op != Op::Get {
+                    self.deadlines.remove(&event.key);
+                }
+            }
+            stats.on_event(index, &event, outcome);
+            observer.on_event(index, &event, outcome);
+
+            undrained |= outcome.inserted();
+            last_index = Some(index);
+            if undrained && (index + 1) % self.drain_every == 0 {
+                self.drain(cache, index, &mut stats, observer);
+                undrained = false;
             }
-            Op::Delete => {
-                cache.delete(event.key);
+        }
+        if let (true, Some(index)) = (undrained, last_index) {
+            self.drain(cache, index, &mut stats, observer);
+        }
+
+        stats
+    }
+
+    /// Report the keys the model evicted since the last drain with `index`.
+    #[inline]
+    fn drain<C, O>(&mut self, cache: &mut C, index: u64, stats: &mut HitStats, observer: &mut O)
+    where
+        C: CacheModel + ?Sized,
+        O: SimObserver + ?Sized,
+    {
+        cache.drain_evictions(&mut self.evicted);
+        for &key in &self.evicted {
+            stats.on_evict(index, key);
+            observer.on_evict(index, key);
+            // Evicted entries can no longer expire.
+            if self.mode.expiry != Expiry::Never {
+                self.deadlines.remove(&key);
             }
         }
+        self.evicted.clear();
     }
 
+    /// Insert the event's key, with its weight in weighted mode.
+    #[inline]
+    fn fill<C: CacheModel + ?Sized>(&self, cache: &mut C, event: &Event) {
+        if self.mode.weighted {
+            cache.insert_weighted(event.key, event.weight.unwrap_or(1));
+        } else {
+            cache.insert(event.key);
+        }
+    }
+
+    /// Advance the clock to the event's timestamp and delete every entry
+    /// whose deadline has passed, reporting it with the event's index.
+    ///
+    /// When the mode checks residency, keys the model already dropped without
+    /// reporting an eviction are forgotten without counting an expiration.
+    #[inline]
+    fn expire<C, O>(
+        &mut self,
+        cache: &mut C,
+        event: &Event,
+        index: u64,
+        stats: &mut HitStats,
+        observer: &mut O,
+    ) where
+        C: CacheModel + ?Sized,
+        O: SimObserver + ?Sized,
+    {
+        if let Some(ts) = event.ts {
+            if ts > self.now {
+                self.now = ts;
+                cache.advance_time(ts);
+            }
+        }
+        while let Some(&Reverse((deadline, key))) = self.expiring.peek() {
+            if deadline > self.now {
+                break;
+            }
+            self.expiring.pop();
+            if self.deadlines.get(&key) != Some(&deadline) {
+                continue;
+            }
+            self.deadlines.remove(&key);
+            if !self.mode.checks_residency() || cache.contains(key) {
+                cache.delete(key);
+                stats.on_expire(index, key);
+                observer.on_expire(index, key);
+                if self.mode.classify_misses {
+                    self.expired.insert(key);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn arm(&mut self, key: u64, ttl: Option<u64>) {
+        match ttl {
+            Some(ttl) => {
+                let deadline = self.now.saturating_add(ttl);
+                self.deadlines.insert(key, deadline);
+                self.expiring.push(Reverse((deadline, key)));
+            }
+            None => {
+                self.deadlines.remove(&key);
+            }
+        }
+    }
+}
+
+/// Events read from the source per batch in multi-model simulation.
+const MANY_BATCH_SIZE: usize = 16 * 1024;
+
+/// Batches buffered per worker before the reader blocks.
+const MANY_QUEUE_DEPTH: usize = 4;
+
+/// Replay one trace through several models, reading each event only once.
+///
+/// Same semantics as [`simulate`] for every model. Events are read in batches
+/// and each batch is replayed through every model in turn, so all models see
+/// exactly the same sequence. Returns one `HitStats` per model, in order.
+pub fn simulate_many<C, S>(caches: &mut [C], source: &mut S) -> Vec<HitStats>
+where
+    C: CacheModel,
+    S: EventSource + ?Sized,
+{
+    let mode = ReplayMode::default();
+    let mut observers = vec![(); caches.len()];
+    let mut replays: Vec<Replay> = caches.iter().map(|_| Replay::new(mode)).collect();
+    let mut stats = vec![HitStats::default(); caches.len()];
+    let mut classifier = mode.classify_misses.then(MissClassifier::default);
+    let mut batch = Vec::with_capacity(MANY_BATCH_SIZE);
+    let mut first_index = 0u64;
+    while next_batch(source, &mut classifier, mode, &mut batch) {
+        replay_batch(
+            caches,
+            &mut observers,
+            &mut replays,
+            &batch,
+            first_index,
+            &mut stats,
+        );
+        first_index += batch.len() as u64;
+    }
     stats
 }
 
-/// Run a simulation without auto-insert on miss.
+/// [`simulate_many`] with the models spread over `threads` worker threads.
 ///
-/// Use this when the trace explicitly contains Insert events and you don't
-/// want automatic insertion on cache misses.
-pub fn simulate_explicit<C, S>(cache: &mut C, source: &mut S) -> HitStats
+/// The calling thread reads the source and hands each batch to every worker
+/// through a bounded queue, so memory stays bounded however long the trace
+/// is. With `threads <= 1` everything runs on the calling thread.
+pub fn simulate_many_threaded<C, S>(
+    caches: &mut [C],
+    source: &mut S,
+    threads: usize,
+) -> Vec<HitStats>
+where
+    C: CacheModel + Send,
+    S: EventSource + ?Sized,
+{
+    let mut observers = vec![(); caches.len()];
+    simulate_many_observed(
+        caches,
+        &mut observers,
+        source,
+        ReplayMode::default(),
+        threads,
+    )
+}
+
+/// [`simulate_many_threaded`] with a [`SimObserver`] per model and a
+/// [`ReplayMode`] shared by all of them.
+///
+/// `observers[i]` sees the events and outcomes of `caches[i]`, indexed from
+/// the start of the trace.
+///
+/// # Panics
+/// Panics if `caches` and `observers` differ in length.
+pub fn simulate_many_observed<C, O, S>(
+    caches: &mut [C],
+    observers: &mut [O],
+    source: &mut S,
+    mode: impl Into<ReplayMode>,
+    threads: usize,
+) -> Vec<HitStats>
+where
+    C: CacheModel + Send,
+    O: SimObserver + Send,
+    S: EventSource + ?Sized,
+{
+    assert_eq!(
+        caches.len(),
+        observers.len(),
+        "one observer is required per model"
+    );
+    let mode = mode.into();
+    let mut replays: Vec<Replay> = caches.iter().map(|_| Replay::new(mode)).collect();
+    let mut stats = vec![HitStats::default(); caches.len()];
+    let mut classifier = mode.classify_misses.then(MissClassifier::default);
+    let mut batch = Vec::with_capacity(MANY_BATCH_SIZE);
+
+    let threads = threads.min(caches.len());
+    if threads <= 1 {
+        let mut first_index = 0u64;
+        while next_batch(source, &mut classifier, mode, &mut batch) {
+            replay_batch(
+                caches,
+                observers,
+                &mut replays,
+                &batch,
+                first_index,
+                &mut stats,
+            );
+            first_index += batch.len() as u64;
+        }
+        return stats;
+    }
+
+    let chunk = caches.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let mut senders = Vec::with_capacity(threads);
+        for (((caches, observers), replays), stats) in caches
+            .chunks_mut(chunk)
+            .zip(observers.chunks_mut(chunk))
+            .zip(replays.chunks_mut(chunk))
+            .zip(stats.chunks_mut(chunk))
+        {
+            let (tx, rx) =
+                mpsc::sync_channel::<Arc<Vec<(Event, Option<MissKind>)>>>(MANY_QUEUE_DEPTH);
+            senders.push(tx);
+            scope.spawn(move || {
+                let mut first_index = 0u64;
+                for batch in rx {
+                    replay_batch(caches, observers, replays, &batch, first_index, stats);
+                    first_index += batch.len() as u64;
+                }
+            });
+        }
+
+        while next_batch(source, &mut classifier, mode, &mut batch) {
+            let shared = Arc::new(std::mem::take(&mut batch));
+            // A send only fails if a worker panicked; the scope re-raises it.
+            if senders
+                .iter()
+                .any(|tx| tx.send(Arc::clone(&shared)).is_err())
+            {
+                break;
+            }
+            batch = Arc::try_unwrap(shared).unwrap_or_else(|_| Vec::with_capacity(MANY_BATCH_SIZE));
+        }
+        drop(senders);
+    });
+    stats
+}
+
+/// Pair `event` with the kind a miss on it would have, if misses are classified.
+#[inline]
+fn classified(
+    classifier: &mut Option<MissClassifier>,
+    event: Event,
+    mode: ReplayMode,
+) -> (Event, Option<MissKind>) {
+    let kind = classifier.as_mut().map(|c| c.classify(&event, mode));
+    (event, kind)
+}
+
+/// Refill `batch` with classified events from `source`; returns `false` once
+/// the source is exhausted.
+fn next_batch<S: EventSource + ?Sized>(
+    source: &mut S,
+    classifier: &mut Option<MissClassifier>,
+    mode: ReplayMode,
+    batch: &mut Vec<(Event, Option<MissKind>)>,
+) -> bool {
+    batch.clear();
+    while batch.len() < MANY_BATCH_SIZE {
+        match source.next_event() {
+            Some(event) => batch.push(classified(classifier, event, mode)),
+            None => break,
+        }
+    }
+    !batch.is_empty()
+}
+
+fn replay_batch<C: CacheModel, O: SimObserver>(
+    caches: &mut [C],
+    observers: &mut [O],
+    replays: &mut [Replay],
+    batch: &[(Event, Option<MissKind>)],
+    first_index: u64,
+    stats: &mut [HitStats],
+) {
+    let models = caches.iter_mut().zip(observers).zip(replays).zip(stats);
+    for (((cache, observer), replay), stats) in models {
+        *stats += replay.run(cache, batch.iter().copied(), observer, first_index);
+    }
+}
+
+/// Run a size-aware simulation, returning object and byte hit statistics.
+///
+/// Behaves like [`simulate`] but inserts through
+/// [`CacheModel::insert_weighted`] and accumulates `hit_bytes`/`miss_bytes`
+/// from each event's weight (default 1). Models that only understand unit
+/// weights still work via the default `insert_weighted` fallback, although
+/// their byte hit ratio will reflect object-count capacity. Use
+/// [`simulate_observed`] with [`ReplayMode::weighted`] to combine weights
+/// with observers, write policies or warmup.
+pub fn simulate_weighted<C, S>(cache: &mut C, source: &mut S) -> HitStats
 where
     C: CacheModel,
     S: EventSource,
 {
-    let mut stats = HitStats::default();
+    let mode = ReplayMode {
+        weighted: true,
+        ..ReplayMode::default()
+    };
+    simulate_observed(cache, source, mode, &mut ())
+}
 
-    while let Some(event) = source.next_event() {
-        match event.op {
-            Op::Get => {
-                if cache.get(event.key) {
-                    stats.hits += 1;
-                } else {
-                    stats.misses += 1;
-                }
+/// Run a time-aware simulation with per-insert TTLs.
+///
+/// Behaves like [`simulate`] under [`Expiry::Ttl`] with `default_ttl`:
+/// entries are removed as soon as the clock passes their deadline and counted
+/// in `HitStats::expirations`, so their next access is a miss. Use
+/// [`simulate_observed`] with [`ReplayMode::expiry`] to combine TTLs with
+/// observers, write policies or warmup.
+pub fn simulate_timed<C, S>(cache: &mut C, source: &mut S, default_ttl: Option<u64>) -> HitStats
+where
+    C: CacheModel,
+    S: EventSource,
+{
+    let mode = ReplayMode {
+        expiry: Expiry::Ttl { default_ttl },
+        ..ReplayMode::default()
+    };
+    simulate_observed(cache, source, mode, &mut ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+    use std::collections::HashMap;
+
+    /// Adapts an expiry callback `FnMut(key, index)` into an observer.
+    struct OnExpire<F>(F);
+
+    impl<F: FnMut(u64, u64)> SimObserver for OnExpire<F> {
+        fn on_event(&mut self, _index: u64, _event: &Event, _outcome: Outcome) {}
+
+        fn on_expire(&mut self, index: u64, key: u64) {
+            (self.0)(key, index)
+        }
+    }
+
+    /// Byte-capacity FIFO used to exercise weighted simulation.
+    struct ByteFifo {
+        capacity: u64,
+        used: u64,
+        sizes: HashMap<u64, u32>,
+        order: Vec<u64>,
+        evicted: Vec<u64>,
+    }
+
+    impl ByteFifo {
+        fn new(capacity: u64) -> Self {
+            Self {
+                capacity,
+                used: 0,
+                sizes: HashMap::new(),
+                order: Vec::new(),
+                evicted: Vec::new(),
+            }
+        }
+    }
+
+    impl CacheModel for ByteFifo {
+        fn get(&mut self, key: u64) -> bool {
+            self.sizes.contains_key(&key)
+        }
+
+        fn insert(&mut self, key: u64) {
+            self.insert_weighted(key, 1);
+        }
+
+        fn insert_weighted(&mut self, key: u64, weight: u32) {
+            if self.sizes.contains_key(&key) {
+                return;
+            }
+            while self.used + weight as u64 > self.capacity && !self.order.is_empty() {
+                let victim = self.order.remove(0);
+                self.used -= self.sizes.remove(&victim).unwrap() as u64;
+                self.evicted.push(victim);
             }
-            Op::Insert => {
-                cache.insert(event.key);
-                stats.inserts += 1;
+            self.sizes.insert(key, weight);
+            self.order.push(key);
+            self.used += weight as u64;
+        }
+
+        fn contains(&self, key: u64) -> bool {
+            self.sizes.contains_key(&key)
+        }
+
+        fn delete(&mut self, key: u64) {
+            if let Some(size) = self.sizes.remove(&key) {
+                self.order.retain(|&k| k != key);
+                self.used -= size as u64;
             }
-            Op::Delete => {
-                cache.delete(event.key);
+        }
+
+        fn drain_evictions(&mut self, out: &mut Vec<u64>) {
+            out.append(&mut self.evicted);
+        }
+    }
+
+    #[test]
+    fn test_simulate_weighted_byte_hit_rate() {
+        let events = vec![
+            Event::get(1).with_weight(100),
+            Event::get(2).with_weight(300),
+            Event::get(1).with_weight(100),
+            Event::get(2).with_weight(300),
+        ];
+        let mut cache = ByteFifo::new(1000);
+        let stats = simulate_weighted(&mut cache, &mut events.into_iter());
+
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.hit_bytes, 400);
+        assert_eq!(stats.miss_bytes, 400);
+        assert!((stats.byte_hit_rate() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simulate_weighted_respects_byte_capacity() {
+        // Large object evicts the small one; the small one then misses.
+        let events = vec![
+            Event::get(1).with_weight(10),
+            Event::get(2).with_weight(95),
+            Event::get(1).with_weight(10),
+        ];
+        let mut cache = ByteFifo::new(100);
+        let stats = simulate_weighted(&mut cache, &mut events.into_iter());
+
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.miss_bytes, 115);
+    }
+
+    #[test]
+    fn test_simulate_counts_evictions() {
+        let events = vec![Event::get(1), Event::get(2), Event::get(3), Event::get(1)];
+        let mut cache = ByteFifo::new(2);
+        let stats = simulate(&mut cache, &mut events.into_iter());
+
+        assert_eq!(stats.inserts, 4);
+        assert_eq!(stats.evictions, 2);
+        let eviction = stats.eviction_stats();
+        assert_eq!(eviction.total_evictions, 2);
+        assert!((eviction.evictions_per_insert - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_write_policies() {
+        let events = vec![
+            Event::insert(1),
+            Event::get(1),
+            Event::insert(1),
+            Event::get(1),
+        ];
+        let run = |writes: WritePolicy| {
+            let mut cache = crate::policies::Lru::new(4);
+            let mode = ReplayMode {
+                writes,
+                classify_misses: true,
+                count_updates: true,
+                ..ReplayMode::default()
+            };
+            simulate_observed(&mut cache, &mut events.clone().into_iter(), mode, &mut ())
+        };
+
+        // Without update counting the driver never asks for residency.
+        let mut cache = crate::policies::Lru::new(4);
+        let stats = simulate_explicit(&mut cache, &mut events.clone().into_iter());
+        assert_eq!((stats.inserts, stats.updates), (2, 0));
+
+        let stats = run(WritePolicy::AllocateOnMiss);
+        assert_eq!((stats.hits, stats.misses), (2, 0));
+        assert_eq!((stats.inserts, stats.updates), (1, 1));
+
+        // The first write is not allocated, the second updates the read copy.
+        let stats = run(WritePolicy::Through);
+        assert_eq!(
+            (stats.hits, stats.misses, stats.compulsory_misses),
+            (1, 1, 1)
+        );
+        assert_eq!((stats.inserts, stats.updates), (1, 1));
+
+        // Writes never reach the cache and invalidate the read copy.
+        let stats = run(WritePolicy::Around);
+        assert_eq!((stats.hits, stats.misses, stats.delete_misses), (0, 2, 1));
+        assert_eq!((stats.inserts, stats.updates), (2, 0));
+    }
+
+    #[test]
+    fn test_simulate_with_evictions_reports_keys_and_positions() {
+        let events = vec![Event::get(1), Event::get(2), Event::get(3), Event::get(1)];
+        let mut cache = ByteFifo::new(2);
+        let mut log = Vec::new();
+        simulate_with_evictions(&mut cache, &mut events.into_iter(), |key, at| {
+            log.push((key, at))
+        });
+
+        assert_eq!(log, vec![(1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_simulate_timed_expires_entries() {
+        let events = vec![
+            Event::get(1).with_ts(0).with_ttl(10),
+            Event::get(1).with_ts(5),
+            Event::get(1).with_ts(10),
+            Event::get(1).with_ts(12),
+        ];
+        let mut cache = ByteFifo::new(100);
+        let stats = simulate_timed(&mut cache, &mut events.into_iter(), None);
+
+        // Miss, hit, expired miss (re-inserted without TTL), hit.
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.expirations, 1);
+    }
+
+    #[test]
+    fn test_ttl_expiry_is_eager() {
+        let events = vec![
+            Event::get(1).with_ts(0).with_ttl(5),
+            Event::get(2).with_ts(10), // clock passes key 1's deadline
+            Event::delete(2).with_ts(11),
+            Event::get(1).with_ts(12),
+        ];
+        let mode = ReplayMode {
+            expiry: Expiry::Ttl { default_ttl: None },
+            classify_misses: true,
+            ..ReplayMode::default()
+        };
+        let mut expired = Vec::new();
+        let mut log = (
+            HitStats::default(),
+            OnExpire(|key, at| expired.push((key, at))),
+        );
+        let stats = simulate_observed(
+            &mut ByteFifo::new(100),
+            &mut events.into_iter(),
+            mode,
+            &mut log,
+        );
+
+        assert_eq!((stats.misses, stats.expirations), (3, 1));
+        assert_eq!((stats.compulsory_misses, stats.delete_misses), (2, 1));
+        assert_eq!(log.0.expirations, 1);
+        assert_eq!(expired, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_ttl_skips_silently_evicted_keys() {
+        /// Forwards to a model but never reports evictions.
+        struct Silent(ByteFifo);
+
+        impl CacheModel for Silent {
+            fn get(&mut self, key: u64) -> bool {
+                self.0.get(key)
+            }
+            fn insert(&mut self, key: u64) {
+                self.0.insert(key)
+            }
+            fn contains(&self, key: u64) -> bool {
+                self.0.contains(key)
+            }
+            fn delete(&mut self, key: u64) {
+                self.0.delete(key)
             }
         }
+
+        let events = [
+            (Event::get(1).with_ts(0).with_ttl(5), None),
+            (Event::get(2).with_ts(1), None), // silently evicts 1
+            (Event::get(3).with_ts(10), None),
+        ];
+        let mut replay = Replay::new(ReplayMode {
+            expiry: Expiry::Ttl { default_ttl: None },
+            count_updates: true,
+            ..ReplayMode::default()
+        });
+        let stats = replay.run(&mut Silent(ByteFifo::new(1)), events, &mut (), 0);
+
+        assert_eq!((stats.evictions, stats.expirations), (0, 0));
+        assert!(replay.deadlines.is_empty());
+        assert!(replay.expiring.is_empty());
     }
 
-    stats
+    #[test]
+    fn test_batched_drains_report_every_eviction() {
+        let events: Vec<(Event, Option<MissKind>)> =
+            (0..10).map(|key| (Event::get(key), None)).collect();
+        let mut drained_at = Vec::new();
+        let mut observer = OnEvict(|_key, at| drained_at.push(at));
+        let stats = Replay::new(ReplayMode::default()).drain_every(4).run(
+            &mut ByteFifo::new(1),
+            events,
+            &mut observer,
+            0,
+        );
+
+        assert_eq!(stats.evictions, 9);
+        drained_at.dedup();
+        assert_eq!(drained_at, vec![3, 7, 9]);
+    }
+
+    #[test]
+    fn test_simulate_timed_default_ttl() {
+        let events = vec![Event::get(1).with_ts(0), Event::get(1).with_ts(100)];
+        let mut cache = ByteFifo::new(100);
+        let stats = simulate_timed(&mut cache, &mut events.into_iter(), Some(50));
+
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.expirations, 1);
+    }
+
+    #[test]
+    fn test_weights_and_ttls_combine_with_observers() {
+        let events = vec![
+            Event::get(1).with_ts(0).with_ttl(10).with_weight(3),
+            Event::get(1).with_ts(5).with_weight(3),
+            Event::get(1).with_ts(10).with_weight(3),
+        ];
+        let mode = ReplayMode {
+            weighted: true,
+            expiry: Expiry::Ttl { default_ttl: None },
+            classify_misses: true,
+            ..ReplayMode::default()
+        };
+        let mut observed = HitStats::default();
+        let stats = simulate_observed(
+            &mut ByteFifo::new(100),
+            &mut events.into_iter(),
+            mode,
+            &mut observed,
+        );
+
+        assert_eq!((stats.hits, stats.misses, stats.expirations), (1, 2, 1));
+        assert_eq!((stats.hit_bytes, stats.miss_bytes), (3, 6));
+        assert_eq!(stats.delete_misses, 1);
+        assert_eq!((observed.hits, observed.misses), (1, 2));
+    }
+
+    #[test]
+    fn test_simulate_weighted_defaults_to_unit_weight() {
+        let events = vec![Event::get(1), Event::get(1)];
+        let mut cache = ByteFifo::new(1);
+        let stats = simulate_weighted(&mut cache, &mut events.into_iter());
+
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.hit_bytes, 1);
+        assert_eq!(stats.miss_bytes, 1);
+    }
+
+    #[test]
+    fn test_simulate_many_matches_individual_runs() {
+        use crate::policies::{Fifo, Lru};
+        use crate::workload::{BoundedGenerator, Workload, WorkloadSpec};
+
+        let spec = WorkloadSpec::new(5_000, Workload::Zipfian { exponent: 0.9 }, 5);
+        // Longer than one batch, so batch boundaries are exercised.
+        let trace = || BoundedGenerator::new(spec.generator(), 40_000);
+        let expected: Vec<HitStats> = [100, 500, 1_000]
+            .into_iter()
+            .flat_map(|capacity| {
+                [
+                    simulate(&mut Lru::new(capacity), &mut trace()),
+                    simulate(&mut Fifo::new(capacity), &mut trace()),
+                ]
+            })
+            .collect();
+        let caches = || -> Vec<Box<dyn CacheModel + Send>> {
+            [100, 500, 1_000]
+                .into_iter()
+                .flat_map(|capacity| -> [Box<dyn CacheModel + Send>; 2] {
+                    [Box::new(Lru::new(capacity)), Box::new(Fifo::new(capacity))]
+                })
+                .collect()
+        };
+
+        assert_eq!(simulate_many(&mut caches(), &mut trace()), expected);
+        assert_eq!(
+            simulate_many_threaded(&mut caches(), &mut trace(), 4),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_simulate_many_observed_indexes_across_batches() {
+        let events: Vec<Event> = (0..MANY_BATCH_SIZE as u64 + 10).map(Event::get).collect();
+        let mut caches = vec![ByteFifo::new(1), ByteFifo::new(1)];
+        let mut last_evictions = vec![None; 2];
+        let mut observers: Vec<_> = last_evictions
+            .iter_mut()
+            .map(|last| OnEvict(move |_key, at| *last = Some(at)))
+            .collect();
+        simulate_many_observed(
+            &mut caches,
+            &mut observers,
+            &mut events.into_iter(),
+            OnMiss::Insert,
+            2,
+        );
+        drop(observers);
+
+        let last = Some(MANY_BATCH_SIZE as u64 + 9);
+        assert_eq!(last_evictions, vec![last, last]);
+    }
+
+    #[test]
+    fn test_simulate_warm_excludes_warmup() {
+        let events: Vec<Event> = [1, 2, 1, 2, 3, 1].into_iter().map(Event::get).collect();
+
+        let stats = simulate_warm(
+            &mut ByteFifo::new(2),
+            &mut events.clone().into_iter(),
+            Warmup::Events(2),
+        );
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+
+        // Unknown length: the trace is buffered to resolve the fraction.
+        let stats = simulate_warm(
+            &mut ByteFifo::new(2),
+            &mut events.clone().into_iter(),
+            Warmup::Fraction(0.5),
+        );
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+
+        // Event 4 (key 3) causes the first eviction and is still warmup.
+        let stats = simulate_warm(
+            &mut ByteFifo::new(2),
+            &mut events.into_iter(),
+            Warmup::UntilFull,
+        );
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (0, 1, 1));
+    }
 }
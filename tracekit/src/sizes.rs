@@ -0,0 +1,443 @@
+//! Object size models for generated traces.
+//!
+//! ## Architecture
+//! A [`SizeModel`] attaches a size to every generated Get and Insert through
+//! `Event::weight`, so size-aware policies and
+//! [`simulate_weighted`](crate::simulate_weighted) can be benchmarked on
+//! synthetic data. Sizes come from a separate random stream, so adding a
+//! size model does not change the generated keys.
+//!
+//! ## Stable Sizes
+//! By default every request draws a fresh size. A per-key model instead
+//! derives the size from the key and seed alone, so a key keeps the same size
+//! for the whole trace, as cached objects do. No per-key state is stored.
+//!
+//! ## Distributions
+//! - [`SizeDistribution::Constant`]: every object has the same size
+//! - [`SizeDistribution::Uniform`]: uniform in `[min, max]`
+//! - [`SizeDistribution::LogNormal`]: heavy-bodied, typical of web objects
+//! - [`SizeDistribution::Pareto`]: heavy-tailed, a few huge objects
+//! - [`SizeDistribution::Bimodal`]: small objects mixed with large ones
+//! - [`SizeDistribution::Empirical`]: sizes and weights from a histogram
+//!
+//! Sampled sizes are rounded and clamped to `[1, u32::MAX]`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, LogNormal, Pareto};
+use serde::{Deserialize, Serialize};
+
+use crate::hash::child_seed;
+
+/// Separates the size stream from the key stream of the same seed.
+const SIZE_SALT: u64 = 0x5129_e5d0_7a3c_41f1;
+
+/// Distribution of object sizes, in bytes (or any unit used for capacity).
+///
+/// Serializes with a `kind` tag, e.g. `{"kind": "lognormal", "mu": 8.0, "sigma": 1.5}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SizeDistribution {
+    /// Every object has `size`.
+    Constant { size: u32 },
+    /// Uniform in `[min, max]`.
+    Uniform { min: u32, max: u32 },
+    /// Log-normal: `ln(size)` is normal with mean `mu` and deviation `sigma`.
+    #[serde(rename = "lognormal")]
+    LogNormal { mu: f64, sigma: f64 },
+    /// Pareto with minimum `scale` and tail index `shape`.
+    Pareto { scale: f64, shape: f64 },
+    /// `large` with probability `large_prob`, otherwise `small`.
+    Bimodal {
+        small: u32,
+        large: u32,
+        large_prob: f64,
+    },
+    /// `(size, weight)` buckets; each bucket's size is picked with
+    /// probability proportional to its weight.
+    Empirical { buckets: Vec<(u32, f64)> },
+}
+
+impl SizeDistribution {
+    /// Describe why the parameters are unusable, if they are.
+    fn invalid(&self) -> Option<&'static str> {
+        match self {
+            SizeDistribution::Constant { .. } => None,
+            SizeDistribution::Uniform { min, max } => (min > max).then_some("min exceeds max"),
+            SizeDistribution::LogNormal { mu, sigma } => {
+                (!mu.is_finite() || !sigma.is_finite() || *sigma < 0.0)
+                    .then_some("sigma must be finite and non-negative")
+            }
+            SizeDistribution::Pareto { scale, shape } => {
+                (!(*scale > 0.0 && *shape > 0.0) || !scale.is_finite() || !shape.is_finite())
+                    .then_some("scale and shape must be positive")
+            }
+            SizeDistribution::Bimodal { large_prob, .. } => {
+                (!(0.0..=1.0).contains(large_prob)).then_some("large_prob must be in [0, 1]")
+            }
+            SizeDistribution::Empirical { buckets } => {
+                let valid = buckets.iter().all(|(_, w)| w.is_finite() && *w >= 0.0)
+                    && buckets.iter().any(|(_, w)| *w > 0.0);
+                (!valid).then_some("bucket weights must be non-negative and not all zero")
+            }
+        }
+    }
+}
+
+/// Parses `kind:param=value,...`, e.g. `constant:size=4096`,
+/// `lognormal:mu=8,sigma=1.5` or `empirical:512=0.7,65536=0.3` (size=weight
+/// buckets).
+impl FromStr for SizeDistribution {
+    type Err = ParseSizeError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let err = |reason: String| ParseSizeError(format!("'{spec}': {reason}"));
+        let parsed = ParamSpec::parse(spec).map_err(err)?;
+        let param = |name: &str| parsed.get(name).map_err(err);
+        let size = |name: &str| {
+            parsed
+                .integer(name, u32::MAX as u64)
+                .map(|v| v as u32)
+                .map_err(err)
+        };
+
+        let distribution = match parsed.kind.as_str() {
+            "constant" => SizeDistribution::Constant {
+                size: size("size")?,
+            },
+            "uniform" => SizeDistribution::Uniform {
+                min: size("min")?,
+                max: size("max")?,
+            },
+            "lognormal" | "log_normal" => SizeDistribution::LogNormal {
+                mu: param("mu")?,
+                sigma: param("sigma")?,
+            },
+            "pareto" => SizeDistribution::Pareto {
+                scale: param("scale")?,
+                shape: param("shape")?,
+            },
+            "bimodal" => SizeDistribution::Bimodal {
+                small: size("small")?,
+                large: size("large")?,
+                large_prob: param("large_prob")?,
+            },
+            "empirical" => SizeDistribution::Empirical {
+                buckets: parsed
+                    .params
+                    .iter()
+                    .map(|&(name, weight)| {
+                        let size = name
+                            .parse()
+                            .map_err(|_| err(format!("'{name}' is not a size")))?;
+                        Ok((size, weight))
+                    })
+                    .collect::<Result<_, ParseSizeError>>()?,
+            },
+            _ => return Err(err("unknown size distribution".to_string())),
+        };
+        match distribution.invalid() {
+            Some(reason) => Err(err(reason.to_string())),
+            None => Ok(distribution),
+        }
+    }
+}
+
+/// A `kind:name=value,...` spec split into its kind and numeric parameters.
+pub(crate) struct ParamSpec<'a> {
+    /// Lowercase kind, with `-` normalized to `_`.
+    pub(crate) kind: String,
+    pub(crate) params: Vec<(&'a str, f64)>,
+}
+
+impl<'a> ParamSpec<'a> {
+    pub(crate) fn parse(spec: &'a str) -> Result<Self, String> {
+        let (kind, rest) = spec.split_once(':').unwrap_or((spec, ""));
+        let mut params = Vec::new();
+        for pair in rest.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=value, got '{pair}'"))?;
+            let value = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("invalid number for '{}'", name.trim()))?;
+            params.push((name.trim(), value));
+        }
+        Ok(Self {
+            kind: kind.trim().to_ascii_lowercase().replace('-', "_"),
+            params,
+        })
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Result<f64, String> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, v)| v)
+            .ok_or_else(|| format!("missing parameter '{name}'"))
+    }
+
+    /// Parameter that must be a whole number in `[0, max]`.
+    pub(crate) fn integer(&self, name: &str, max: u64) -> Result<u64, String> {
+        let value = self.get(name)?;
+        if value >= 0.0 && value <= max as f64 && value.fract() == 0.0 {
+            Ok(value as u64)
+        } else {
+            Err(format!("'{name}' must be a whole number up to {max}"))
+        }
+    }
+}
+
+/// Error from parsing a [`SizeDistribution`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSizeError(String);
+
+impl fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid size distribution {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSizeError {}
+
+/// Size distribution plus whether sizes are fixed per key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeModel {
+    /// Distribution sizes are drawn from.
+    pub distribution: SizeDistribution,
+    /// Give each key one size for the whole trace.
+    #[serde(default)]
+    pub per_key: bool,
+}
+
+impl SizeModel {
+    /// A model drawing a fresh size for every request.
+    pub fn new(distribution: SizeDistribution) -> Self {
+        Self {
+            distribution,
+            per_key: false,
+        }
+    }
+
+    /// A model giving each key a stable size.
+    pub fn per_key(distribution: SizeDistribution) -> Self {
+        Self {
+            per_key: true,
+            ..Self::new(distribution)
+        }
+    }
+}
+
+/// Sampling state compiled from a [`SizeDistribution`].
+#[derive(Debug, Clone)]
+enum Sampler {
+    Constant(u32),
+    Uniform(u32, u32),
+    LogNormal(LogNormal<f64>),
+    Pareto(Pareto<f64>),
+    Bimodal(u32, u32, f64),
+    /// Bucket sizes and running weight totals.
+    Empirical(Vec<u32>, Vec<f64>),
+}
+
+/// Draws sizes for a generator.
+#[derive(Debug, Clone)]
+pub(crate) struct SizeSampler {
+    sampler: Sampler,
+    per_key: bool,
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl SizeSampler {
+    /// # Panics
+    /// Panics if the distribution's parameters are invalid.
+    pub(crate) fn new(model: &SizeModel, seed: u64) -> Self {
+        if let Some(reason) = model.distribution.invalid() {
+            panic!(
+                "invalid size distribution {:?}: {reason}",
+                model.distribution
+            );
+        }
+        let sampler = match &model.distribution {
+            SizeDistribution::Constant { size } => Sampler::Constant(*size),
+            SizeDistribution::Uniform { min, max } => Sampler::Uniform(*min, *max),
+            SizeDistribution::LogNormal { mu, sigma } => {
+                Sampler::LogNormal(LogNormal::new(*mu, *sigma).unwrap())
+            }
+            SizeDistribution::Pareto { scale, shape } => {
+                Sampler::Pareto(Pareto::new(*scale, *shape).unwrap())
+            }
+            SizeDistribution::Bimodal {
+                small,
+                large,
+                large_prob,
+            } => Sampler::Bimodal(*small, *large, *large_prob),
+            SizeDistribution::Empirical { buckets } => {
+                let mut total = 0.0;
+                let cumulative = buckets
+                    .iter()
+                    .map(|(_, weight)| {
+                        total += weight;
+                        total
+                    })
+                    .collect();
+                Sampler::Empirical(buckets.iter().map(|&(size, _)| size).collect(), cumulative)
+            }
+        };
+        Self {
+            sampler,
+            per_key: model.per_key,
+            seed: seed ^ SIZE_SALT,
+            rng: SmallRng::seed_from_u64(seed ^ SIZE_SALT),
+        }
+    }
+
+    /// Size of the next request for `key`.
+    pub(crate) fn size(&mut self, key: u64) -> u32 {
+        if self.per_key {
+            let mut rng = SmallRng::seed_from_u64(child_seed(self.seed, key));
+            self.sampler.sample(&mut rng)
+        } else {
+            self.sampler.sample(&mut self.rng)
+        }
+    }
+}
+
+impl Sampler {
+    fn sample(&self, rng: &mut SmallRng) -> u32 {
+        let size = match self {
+            Sampler::Constant(size) => return (*size).max(1),
+            Sampler::Uniform(min, max) => return rng.random_range(*min..=*max).max(1),
+            Sampler::LogNormal(distribution) => distribution.sample(rng),
+            Sampler::Pareto(distribution) => distribution.sample(rng),
+            Sampler::Bimodal(small, large, large_prob) => {
+                let size = if rng.random::<f64>() < *large_prob {
+                    large
+                } else {
+                    small
+                };
+                return (*size).max(1);
+            }
+            Sampler::Empirical(sizes, cumulative) => {
+                let pick = rng.random::<f64>() * cumulative[cumulative.len() - 1];
+                let idx = cumulative
+                    .iter()
+                    .position(|&bound| pick < bound)
+                    .unwrap_or(sizes.len() - 1);
+                return sizes[idx].max(1);
+            }
+        };
+        size.round().clamp(1.0, u32::MAX as f64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_serde() {
+        let parsed: SizeDistribution = "lognormal:mu=8,sigma=1.5".parse().unwrap();
+        assert_eq!(
+            parsed,
+            SizeDistribution::LogNormal {
+                mu: 8.0,
+                sigma: 1.5
+            }
+        );
+        let parsed: SizeDistribution = "empirical:512=0.7,65536=0.3".parse().unwrap();
+        assert_eq!(
+            parsed,
+            SizeDistribution::Empirical {
+                buckets: vec![(512, 0.7), (65536, 0.3)]
+            }
+        );
+        assert!("uniform:min=10".parse::<SizeDistribution>().is_err());
+        assert!("uniform:min=10,max=5".parse::<SizeDistribution>().is_err());
+        assert!(
+            "pareto:scale=0,shape=1"
+                .parse::<SizeDistribution>()
+                .is_err()
+        );
+        assert!("normal:mu=1".parse::<SizeDistribution>().is_err());
+
+        let model = SizeModel::per_key(parsed);
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(serde_json::from_str::<SizeModel>(&json).unwrap(), model);
+        let model: SizeModel =
+            serde_json::from_str(r#"{"distribution": {"kind": "constant", "size": 64}}"#).unwrap();
+        assert_eq!(
+            model,
+            SizeModel::new(SizeDistribution::Constant { size: 64 })
+        );
+    }
+
+    #[test]
+    fn test_sizes_stay_in_range() {
+        let uniform = SizeModel::new(SizeDistribution::Uniform { min: 10, max: 20 });
+        let mut sampler = SizeSampler::new(&uniform, 1);
+        assert!((0..1_000).all(|key| (10..=20).contains(&sampler.size(key))));
+
+        let bimodal = SizeModel::new(SizeDistribution::Bimodal {
+            small: 100,
+            large: 10_000,
+            large_prob: 0.25,
+        });
+        let mut sampler = SizeSampler::new(&bimodal, 1);
+        let sizes: Vec<u32> = (0..4_000).map(|key| sampler.size(key)).collect();
+        assert!(sizes.iter().all(|&s| s == 100 || s == 10_000));
+        let large = sizes.iter().filter(|&&s| s == 10_000).count();
+        assert!((800..1_200).contains(&large), "{large}");
+
+        let pareto = SizeModel::new(SizeDistribution::Pareto {
+            scale: 1_000.0,
+            shape: 1.2,
+        });
+        let mut sampler = SizeSampler::new(&pareto, 1);
+        assert!((0..1_000).all(|key| sampler.size(key) >= 1_000));
+    }
+
+    #[test]
+    fn test_per_key_sizes_are_stable() {
+        let distribution = SizeDistribution::LogNormal {
+            mu: 8.0,
+            sigma: 2.0,
+        };
+        let mut stable = SizeSampler::new(&SizeModel::per_key(distribution.clone()), 7);
+        let first: Vec<u32> = (0..100).map(|key| stable.size(key)).collect();
+        let again: Vec<u32> = (0..100).map(|key| stable.size(key)).collect();
+        assert_eq!(first, again);
+
+        let mut fresh = SizeSampler::new(&SizeModel::new(distribution), 7);
+        let repeated: Vec<u32> = (0..100).map(|_| fresh.size(5)).collect();
+        assert!(repeated.iter().any(|&s| s != repeated[0]));
+    }
+
+    #[test]
+    fn test_generator_keeps_keys() {
+        use crate::source::EventSource;
+        use crate::workload::{Workload, WorkloadSpec};
+
+        let spec = WorkloadSpec::new(1_000, Workload::Zipfian { exponent: 0.9 }, 3);
+        let sized = spec
+            .clone()
+            .with_sizes(SizeModel::per_key(SizeDistribution::Uniform {
+                min: 1,
+                max: 9,
+            }));
+        let (mut plain, mut sized) = (spec.generator(), sized.generator());
+        for _ in 0..1_000 {
+            let (a, b) = (plain.next_event().unwrap(), sized.next_event().unwrap());
+            assert_eq!(a.key, b.key);
+            assert_eq!(a.weight, None);
+            assert!((1..=9).contains(&b.weight.unwrap()));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Windowed time-series statistics.
+//!
+//! ## Architecture
+//! [`WindowedStats`] splits a replay into consecutive windows, either every
+//! N events or by fixed-width [`Event::ts`] intervals, and keeps a
+//! [`HitStats`] per window. [`simulate_windowed`](crate::simulate_windowed)
+//! feeds it and hands every completed window to a callback, so a long trace
+//! can be streamed without holding the whole series in memory. To combine it
+//! with other observers, use [`WindowedStats::observer`].
+//!
+//! ## Window Boundaries
+//! - [`WindowBy::Events`]: a window closes after exactly N events
+//! - [`WindowBy::Time`]: a window covers `[k * width, (k + 1) * width)` of
+//!   timestamp space. Events without a timestamp, or with a timestamp that
+//!   goes backwards, stay in the current window. Windows with no events are
+//!   not reported, so gaps in the trace show up as gaps in `start_ts`.
+//!
+//! State is kept across calls, so a trace replayed in batches produces the
+//! same series as a single replay. Call [`WindowedStats::finish`] at the end
+//! to flush the last, usually partial, window.
+
+use crate::event::Event;
+use crate::metrics::HitStats;
+use crate::observer::{Outcome, SimObserver};
+
+/// How a replay is split into windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowBy {
+    /// Fixed number of events per window.
+    Events(u64),
+    /// Fixed-width timestamp intervals, in `Event::ts` units.
+    Time(u64),
+}
+
+/// Statistics for one window of a replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStats {
+    /// Zero-based window sequence number.
+    pub index: usize,
+    /// Zero-based index of the window's first event within the replay.
+    pub first_event: u64,
+    /// Number of events in the window.
+    pub events: u64,
+    /// Window start: the interval start for time windows, otherwise the
+    /// first timestamp seen (if any).
+    pub start_ts: Option<u64>,
+    /// Last timestamp seen in the window.
+    pub end_ts: Option<u64>,
+    /// Hit/miss counts for the window.
+    pub stats: HitStats,
+}
+
+/// Incremental per-window statistics recorder.
+#[derive(Debug, Clone)]
+pub struct WindowedStats {
+    by: WindowBy,
+    next_index: usize,
+    next_event: u64,
+    /// Interval number of the open window, for time windows.
+    interval: Option<u64>,
+    current: Option<WindowStats>,
+}
+
+impl WindowedStats {
+    /// Create a recorder. Window sizes of zero are treated as one.
+    pub fn new(by: WindowBy) -> Self {
+        let by = match by {
+            WindowBy::Events(n) => WindowBy::Events(n.max(1)),
+            WindowBy::Time(width) => WindowBy::Time(width.max(1)),
+        };
+        Self {
+            by,
+            next_index: 0,
+            next_event: 0,
+            interval: None,
+            current: None,
+        }
+    }
+
+    /// Window specification in use.
+    pub fn window_by(&self) -> WindowBy {
+        self.by
+    }
+
+    /// Close and return the open window, if it has any events.
+    pub fn finish(&mut self) -> Option<WindowStats> {
+        self.interval = None;
+        let window = self.current.take()?;
+        self.next_index += 1;
+        Some(window)
+    }
+
+    /// Assign `event` to a window, returning the window it closed, if any.
+    fn open(&mut self, event: &Event) -> Option<WindowStats> {
+        let mut closed = None;
+        match self.by {
+            WindowBy::Events(n) => {
+                if self.current.is_some_and(|w| w.events >= n) {
+                    closed = self.finish();
+                }
+            }
+            WindowBy::Time(width) => {
+                if let Some(interval) = event.ts.map(|ts| ts / width) {
+                    match self.interval {
+                        Some(open) if interval > open => {
+                            closed = self.finish();
+                            self.interval = Some(interval);
+                        }
+                        Some(_) => {}
+                        None => self.interval = Some(interval),
+                    }
+                }
+            }
+        }
+
+        let start_ts = match self.by {
+            WindowBy::Time(width) => self.interval.map(|interval| interval * width),
+            WindowBy::Events(_) => event.ts,
+        };
+        let window = self.current.get_or_insert(WindowStats {
+            index: self.next_index,
+            first_event: self.next_event,
+            events: 0,
+            start_ts,
+            end_ts: None,
+            stats: HitStats::default(),
+        });
+        // A time window opened by untimestamped events learns its start late.
+        if window.start_ts.is_none() {
+            window.start_ts = start_ts;
+        }
+        window.events += 1;
+        if event.ts.is_some() {
+            window.end_ts = event.ts;
+        }
+        self.next_event += 1;
+        closed
+    }
+
+    /// Observer that feeds this recorder and passes closed windows to `on_window`.
+    pub fn observer<F: FnMut(WindowStats)>(&mut self, on_window: F) -> WindowObserver<'_, F> {
+        WindowObserver {
+            windows: self,
+            on_window,
+        }
+    }
+}
+
+/// [`SimObserver`] view of a [`WindowedStats`], from [`WindowedStats::observer`].
+pub struct WindowObserver<'a, F> {
+    windows: &'a mut WindowedStats,
+    on_window: F,
+}
+
+impl<F: FnMut(WindowStats)> SimObserver for WindowObserver<'_, F> {
+    fn on_event(&mut self, index: u64, event: &Event, outcome: Outcome) {
+        if let Some(closed) = self.windows.open(event) {
+            (self.on_window)(closed);
+        }
+        if let Some(window) = &mut self.windows.current {
+            window.stats.on_event(index, event, outcome);
+        }
+    }
+
+    fn on_evict(&mut self, index: u64, key: u64) {
+        if let Some(window) = &mut self.windows.current {
+            window.stats.on_evict(index, key);
+        }
+    }
+
+    fn on_expire(&mut self, index: u64, key: u64) {
+        if let Some(window) = &mut self.windows.current {
+            window.stats.on_expire(index, key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policies::Lru;
+    use crate::simulate_windowed;
+
+    fn collect(by: WindowBy, events: Vec<Event>) -> Vec<WindowStats> {
+        let mut cache = Lru::new(10);
+        let mut windows = WindowedStats::new(by);
+        let mut series = Vec::new();
+        simulate_windowed(&mut cache, &mut events.into_iter(), &mut windows, |w| {
+            series.push(w)
+        });
+        series.extend(windows.finish());
+        series
+    }
+
+    #[test]
+    fn test_event_windows() {
+        let events = (0..10).map(|i| Event::get(i % 3)).collect();
+        let series = collect(WindowBy::Events(4), events);
+
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[0].stats.misses, 3);
+        assert_eq!(series[0].stats.hits, 1);
+        assert_eq!(series[1].first_event, 4);
+        assert_eq!(series[1].stats.hits, 4);
+        assert_eq!(series[2].events, 2);
+    }
+
+    #[test]
+    fn test_time_windows_skip_gaps() {
+        let events = vec![
+            Event::get(1).with_ts(5),
+            Event::get(1).with_ts(9),
+            Event::get(1),
+            Event::get(2).with_ts(35),
+            Event::get(2).with_ts(30),
+        ];
+        let series = collect(WindowBy::Time(10), events);
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].start_ts, Some(0));
+        assert_eq!(series[0].events, 3);
+        assert_eq!(series[0].end_ts, Some(9));
+        assert_eq!(series[1].index, 1);
+        assert_eq!(series[1].start_ts, Some(30));
+        assert_eq!(series[1].stats.hits, 1);
+    }
+
+    #[test]
+    fn test_batched_replay_matches_single_pass() {
+        let events: Vec<Event> = (0..100).map(|i| Event::get(i * 7 % 13)).collect();
+        let whole = collect(WindowBy::Events(16), events.clone());
+
+        let mut cache = Lru::new(10);
+        let mut windows = WindowedStats::new(WindowBy::Events(16));
+        let mut series = Vec::new();
+        for batch in events.chunks(10) {
+            simulate_windowed(&mut cache, &mut batch.iter().copied(), &mut windows, |w| {
+                series.push(w)
+            });
+        }
+        series.extend(windows.finish());
+        assert_eq!(series, whole);
+    }
+}
@@ -2,11 +2,23 @@
 //!
 //! Provides deterministic key streams for cache benchmarking.
 
+use std::borrow::Cow;
+
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 use rand_distr::{Distribution, Exp, Pareto as ParetoDistr, Zipf};
+use serde::{Deserialize, Serialize};
+
+use crate::arrival::{ArrivalClock, ArrivalModel, TtlModel};
+use crate::mixture::{DEFAULT_MIXTURE, MixtureComponent, MixtureGenerator};
+use crate::op_mix::{MixOp, OpMix, Ycsb};
+use crate::sizes::{SizeModel, SizeSampler};
 
-#[derive(Debug, Clone, Copy)]
+/// Key distribution of a synthetic workload.
+///
+/// Serializes with a `kind` tag, e.g. `{"kind": "zipfian", "exponent": 0.9}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Workload {
     /// Uniform random keys in `[0, universe)`.
     Uniform,
@@ -85,20 +97,283 @@ pub enum Workload {
         /// Multiplier on access probability
         flash_intensity: f64,
     },
-    /// Meta-workload, combines others flexibly
-    Mixture,
+    /// Weighted blend of other workloads.
+    /// Omitted `components` default to 70% Zipfian, 20% scan and 10% uniform
+    /// (see [`DEFAULT_MIXTURE`](crate::mixture::DEFAULT_MIXTURE)).
+    Mixture {
+        #[serde(default = "default_mixture")]
+        components: Cow<'static, [MixtureComponent]>,
+    },
+}
+
+fn default_mixture() -> Cow<'static, [MixtureComponent]> {
+    Cow::Borrowed(&DEFAULT_MIXTURE)
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Workload {
+    /// [`Workload::Mixture`] of the default components.
+    pub const MIXTURE: Workload = Workload::Mixture {
+        components: Cow::Borrowed(&DEFAULT_MIXTURE),
+    };
+
+    /// Variant name, e.g. "Zipfian" or "ScanResistance".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::Uniform => "Uniform",
+            Workload::HotSet { .. } => "HotSet",
+            Workload::Scan => "Scan",
+            Workload::Zipfian { .. } => "Zipfian",
+            Workload::ScrambledZipfian { .. } => "ScrambledZipfian",
+            Workload::Latest { .. } => "Latest",
+            Workload::ShiftingHotspot { .. } => "ShiftingHotspot",
+            Workload::Exponential { .. } => "Exponential",
+            Workload::Pareto { .. } => "Pareto",
+            Workload::ScanResistance { .. } => "ScanResistance",
+            Workload::Correlated { .. } => "Correlated",
+            Workload::Loop { .. } => "Loop",
+            Workload::WorkingSetChurn { .. } => "WorkingSetChurn",
+            Workload::Bursty { .. } => "Bursty",
+            Workload::FlashCrowd { .. } => "FlashCrowd",
+            Workload::Mixture { .. } => "Mixture",
+        }
+    }
+
+    /// Parameters a fraction `t` of the way from `self` to `to`.
+    ///
+    /// Numeric parameters are interpolated linearly, integer ones rounded.
+    /// Returns `None` for workloads of different kinds and for mixtures with
+    /// different components, which do not interpolate.
+    pub fn interpolate(&self, to: &Workload, t: f64) -> Option<Workload> {
+        use Workload as W;
+
+        let t = t.clamp(0.0, 1.0);
+        let f = |a: &f64, b: &f64| a + (b - a) * t;
+        let n = |a: &u64, b: &u64| f(&(*a as f64), &(*b as f64)).round() as u64;
+        let interpolated = match (self, to) {
+            (
+                W::HotSet {
+                    hot_fraction,
+                    hot_prob,
+                },
+                W::HotSet {
+                    hot_fraction: hot_fraction_to,
+                    hot_prob: hot_prob_to,
+                },
+            ) => W::HotSet {
+                hot_fraction: f(hot_fraction, hot_fraction_to),
+                hot_prob: f(hot_prob, hot_prob_to),
+            },
+            (W::Zipfian { exponent }, W::Zipfian { exponent: to }) => W::Zipfian {
+                exponent: f(exponent, to),
+            },
+            (W::ScrambledZipfian { exponent }, W::ScrambledZipfian { exponent: to }) => {
+                W::ScrambledZipfian {
+                    exponent: f(exponent, to),
+                }
+            }
+            (W::Latest { exponent }, W::Latest { exponent: to }) => W::Latest {
+                exponent: f(exponent, to),
+            },
+            (
+                W::ShiftingHotspot {
+                    shift_interval,
+                    hot_fraction,
+                },
+                W::ShiftingHotspot {
+                    shift_interval: shift_interval_to,
+                    hot_fraction: hot_fraction_to,
+                },
+            ) => W::ShiftingHotspot {
+                shift_interval: n(shift_interval, shift_interval_to),
+                hot_fraction: f(hot_fraction, hot_fraction_to),
+            },
+            (W::Exponential { lambda }, W::Exponential { lambda: to }) => W::Exponential {
+                lambda: f(lambda, to),
+            },
+            (W::Pareto { shape }, W::Pareto { shape: to }) => W::Pareto {
+                shape: f(shape, to),
+            },
+            (
+                W::ScanResistance {
+                    scan_fraction,
+                    scan_length,
+                    point_exponent,
+                },
+                W::ScanResistance {
+                    scan_fraction: scan_fraction_to,
+                    scan_length: scan_length_to,
+                    point_exponent: point_exponent_to,
+                },
+            ) => W::ScanResistance {
+                scan_fraction: f(scan_fraction, scan_fraction_to),
+                scan_length: n(scan_length, scan_length_to),
+                point_exponent: f(point_exponent, point_exponent_to),
+            },
+            (
+                W::Correlated {
+                    stride,
+                    burst_len,
+                    burst_prob,
+                },
+                W::Correlated {
+                    stride: stride_to,
+                    burst_len: burst_len_to,
+                    burst_prob: burst_prob_to,
+                },
+            ) => W::Correlated {
+                stride: n(stride, stride_to),
+                burst_len: n(burst_len, burst_len_to),
+                burst_prob: f(burst_prob, burst_prob_to),
+            },
+            (
+                W::Loop { working_set_size },
+                W::Loop {
+                    working_set_size: to,
+                },
+            ) => W::Loop {
+                working_set_size: n(working_set_size, to),
+            },
+            (
+                W::WorkingSetChurn {
+                    working_set_size,
+                    churn_rate,
+                },
+                W::WorkingSetChurn {
+                    working_set_size: working_set_size_to,
+                    churn_rate: churn_rate_to,
+                },
+            ) => W::WorkingSetChurn {
+                working_set_size: n(working_set_size, working_set_size_to),
+                churn_rate: f(churn_rate, churn_rate_to),
+            },
+            (
+                W::Bursty {
+                    hurst,
+                    base_exponent,
+                },
+                W::Bursty {
+                    hurst: hurst_to,
+                    base_exponent: base_exponent_to,
+                },
+            ) => W::Bursty {
+                hurst: f(hurst, hurst_to),
+                base_exponent: f(base_exponent, base_exponent_to),
+            },
+            (
+                W::FlashCrowd {
+                    base_exponent,
+                    flash_prob,
+                    flash_duration,
+                    flash_keys,
+                    flash_intensity,
+                },
+                W::FlashCrowd {
+                    base_exponent: base_exponent_to,
+                    flash_prob: flash_prob_to,
+                    flash_duration: flash_duration_to,
+                    flash_keys: flash_keys_to,
+                    flash_intensity: flash_intensity_to,
+                },
+            ) => W::FlashCrowd {
+                base_exponent: f(base_exponent, base_exponent_to),
+                flash_prob: f(flash_prob, flash_prob_to),
+                flash_duration: n(flash_duration, flash_duration_to),
+                flash_keys: n(flash_keys, flash_keys_to),
+                flash_intensity: f(flash_intensity, flash_intensity_to),
+            },
+            (from, to) if from == to => from.clone(),
+            _ => return None,
+        };
+        Some(interpolated)
+    }
+}
+
+/// Parameters of a generated workload.
+///
+/// Build one with [`WorkloadSpec::new`] (or [`Default`]) and the `with_*`
+/// builders; new fields may be added, so it cannot be built as a struct
+/// literal outside this crate.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct WorkloadSpec {
     pub universe: u64,
     pub workload: Workload,
     pub seed: u64,
+    /// Operations emitted by the generator; read-only by default.
+    pub ops: OpMix,
+    /// Object sizes set as `Event::weight`; `None` leaves events unweighted.
+    pub sizes: Option<SizeModel>,
+    /// Arrival times set as `Event::ts`; `None` leaves events untimed.
+    pub arrivals: Option<ArrivalModel>,
+    /// Per-key TTLs set as `Event::ttl`.
+    pub ttl: Option<TtlModel>,
 }
 
 impl WorkloadSpec {
-    pub fn generator(self) -> WorkloadGenerator {
-        WorkloadGenerator::new(self.universe, self.workload, self.seed)
+    /// A read-only spec.
+    pub fn new(universe: u64, workload: Workload, seed: u64) -> Self {
+        Self {
+            universe,
+            workload,
+            seed,
+            ops: OpMix::READ_ONLY,
+            sizes: None,
+            arrivals: None,
+            ttl: None,
+        }
+    }
+
+    /// A YCSB core workload: the preset's key distribution and op mix.
+    ///
+    /// Fresh-key inserts (workloads D and E) write keys above `universe`.
+    pub fn ycsb(preset: Ycsb, universe: u64, seed: u64) -> Self {
+        Self::new(universe, preset.workload(), seed).with_ops(preset.op_mix())
+    }
+
+    /// Replace the operation mix.
+    pub fn with_ops(mut self, ops: OpMix) -> Self {
+        self.ops = ops;
+        self
+    }
+
+    /// Attach object sizes to generated events.
+    pub fn with_sizes(mut self, sizes: SizeModel) -> Self {
+        self.sizes = Some(sizes);
+        self
+    }
+
+    /// Stamp generated events with arrival times.
+    pub fn with_arrivals(mut self, arrivals: ArrivalModel) -> Self {
+        self.arrivals = Some(arrivals);
+        self
+    }
+
+    /// Give every key a TTL.
+    pub fn with_ttl(mut self, ttl: TtlModel) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn generator(&self) -> WorkloadGenerator {
+        let mut generator = WorkloadGenerator::new(self.universe, self.workload.clone(), self.seed)
+            .with_ops(self.ops);
+        if let Some(sizes) = &self.sizes {
+            generator = generator.with_sizes(sizes);
+        }
+        if let Some(arrivals) = self.arrivals {
+            generator = generator.with_arrivals(arrivals);
+        }
+        if let Some(ttl) = self.ttl {
+            generator = generator.with_ttl(ttl);
+        }
+        generator
+    }
+}
+
+/// Zipfian (1.0) reads over 16,384 keys with seed 42.
+impl Default for WorkloadSpec {
+    fn default() -> Self {
+        Self::new(16_384, Workload::Zipfian { exponent: 1.0 }, 42)
     }
 }
 
@@ -110,6 +385,8 @@ pub struct WorkloadGenerator {
     scan_pos: u64,
     operation_count: u64,
     insert_counter: u64,
+    /// Fresh keys inserted by the op mix, numbered from `universe + 1`.
+    fresh: u64,
     zipfian: Option<Zipf<f64>>,
     exponential: Option<Exp<f64>>,
     pareto: Option<ParetoDistr<f64>>,
@@ -133,6 +410,17 @@ pub struct WorkloadGenerator {
     in_scan: bool,
     scan_ops_remaining: u64,
     scan_start_key: u64,
+    // Mixture state
+    mixture: Option<Box<MixtureGenerator>>,
+    // Operation mix state
+    ops: OpMix,
+    /// Write half of a read-modify-write, emitted next.
+    pending: Option<Event>,
+    // Object size, arrival time and TTL state
+    sizes: Option<SizeSampler>,
+    arrivals: Option<ArrivalClock>,
+    ttl: Option<TtlModel>,
+    seed: u64,
 }
 
 impl WorkloadGenerator {
@@ -170,6 +458,12 @@ impl WorkloadGenerator {
             }
             _ => None,
         };
+        let mixture = match &workload {
+            Workload::Mixture { components } => {
+                Some(Box::new(MixtureGenerator::new(universe, components, seed)))
+            }
+            _ => None,
+        };
         Self {
             universe,
             workload,
@@ -177,6 +471,7 @@ impl WorkloadGenerator {
             scan_pos: 0,
             operation_count: 0,
             insert_counter: 0,
+            fresh: 0,
             zipfian,
             exponential,
             pareto,
@@ -194,12 +489,148 @@ impl WorkloadGenerator {
             in_scan: false,
             scan_ops_remaining: 0,
             scan_start_key: 0,
+            mixture,
+            ops: OpMix::READ_ONLY,
+            pending: None,
+            sizes: None,
+            arrivals: None,
+            ttl: None,
+            seed,
         }
     }
 
+    /// Emit operations according to `ops` instead of reads only.
+    pub fn with_ops(mut self, ops: OpMix) -> Self {
+        self.ops = ops;
+        self
+    }
+
+    /// Set `Event::weight` of every Get and Insert from `sizes`.
+    ///
+    /// Sizes use their own random stream, so the keys are the same as
+    /// without a size model.
+    ///
+    /// # Panics
+    /// Panics if the size distribution's parameters are invalid.
+    pub fn with_sizes(mut self, sizes: &SizeModel) -> Self {
+        self.sizes = Some(SizeSampler::new(sizes, self.seed));
+        self
+    }
+
+    /// Set `Event::ts` of every event from `arrivals`.
+    ///
+    /// # Panics
+    /// Panics if the arrival process's parameters are invalid.
+    pub fn with_arrivals(mut self, arrivals: ArrivalModel) -> Self {
+        self.arrivals = Some(ArrivalClock::new(arrivals, self.seed));
+        self
+    }
+
+    /// Set `Event::ttl` of every Get and Insert from `ttl`, stable per key.
+    ///
+    /// # Panics
+    /// Panics if the TTL model's parameters are invalid.
+    pub fn with_ttl(mut self, ttl: TtlModel) -> Self {
+        ttl.validate();
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Create a [`Workload::Mixture`] generator with custom components.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`MixtureGenerator::new`].
+    pub fn mixture(universe: u64, components: &[MixtureComponent], seed: u64) -> Self {
+        let components = Cow::Owned(components.to_vec());
+        Self::new(universe, Workload::Mixture { components }, seed)
+    }
+
+    /// Switch to `workload`, keeping the random stream and positional state
+    /// (scan position, insert counter, ...).
+    ///
+    /// Used to change parameters mid-stream, e.g. by a
+    /// [`WorkloadSchedule`](crate::schedule::WorkloadSchedule).
+    pub fn set_workload(&mut self, workload: Workload) {
+        if workload == self.workload {
+            return;
+        }
+        let fresh = Self::new(self.universe, workload, self.rng.random());
+        self.workload = fresh.workload;
+        self.zipfian = fresh.zipfian;
+        self.exponential = fresh.exponential;
+        self.pareto = fresh.pareto;
+        self.bursty_zipfian = fresh.bursty_zipfian;
+        self.flash_zipfian = fresh.flash_zipfian;
+        self.scan_resistance_zipfian = fresh.scan_resistance_zipfian;
+        self.mixture = fresh.mixture;
+    }
+
+    /// Key universe of the generator.
+    pub fn universe(&self) -> u64 {
+        self.universe
+    }
+
+    /// Current workload.
+    pub fn workload(&self) -> &Workload {
+        &self.workload
+    }
+
+    /// Next operation from the op mix, sized but not yet stamped.
+    fn next_op(&mut self) -> Event {
+        if self.ops.is_read_only() {
+            let key = self.next_key();
+            return self.sized(Event::get(key));
+        }
+        let event = match self.ops.pick(self.rng.random()) {
+            MixOp::Read => Event::get(self.next_key()),
+            MixOp::Update => Event::insert(self.next_key()),
+            MixOp::Insert => {
+                self.fresh += 1;
+                self.record_insert();
+                Event::insert(self.universe + self.fresh)
+            }
+            MixOp::Delete => Event::delete(self.next_key()),
+            MixOp::ReadModifyWrite => {
+                // The write keeps the size the read saw.
+                let key = self.next_key();
+                let read = self.sized(Event::get(key));
+                self.pending = Some(Event {
+                    op: Op::Insert,
+                    ..read
+                });
+                return read;
+            }
+        };
+        self.sized(event)
+    }
+
+    /// Set the event's weight from the size model, if any. Deletes stay unweighted.
+    fn sized(&mut self, event: Event) -> Event {
+        match &mut self.sizes {
+            Some(sizes) if event.op != Op::Delete => event.with_weight(sizes.size(event.key)),
+            _ => event,
+        }
+    }
+
+    /// Set the event's arrival time and TTL, if modeled. Deletes get no TTL.
+    fn stamped(&mut self, mut event: Event) -> Event {
+        if let Some(arrivals) = &mut self.arrivals {
+            event = event.with_ts(arrivals.next_ts(self.burst_active || self.flash_active));
+        }
+        if let Some(ttl) = &self.ttl {
+            if event.op != Op::Delete {
+                event = event.with_ttl(ttl.ttl(self.seed, event.key));
+            }
+        }
+        event
+    }
+
     /// Notify the generator that a key was inserted (for Latest workload).
     pub fn record_insert(&mut self) {
         self.insert_counter = self.insert_counter.wrapping_add(1);
+        if let Some(mixture) = &mut self.mixture {
+            mixture.record_insert();
+        }
     }
 
     pub fn next_key(&mut self) -> u64 {
@@ -249,8 +680,13 @@ impl WorkloadGenerator {
                 let zipf = self.zipfian.as_ref().unwrap();
                 let sample: f64 = zipf.sample(&mut self.rng);
                 let offset = (sample as u64).saturating_sub(1).min(self.universe - 1);
-                // Access keys near the most recent insert, wrapping around
-                self.insert_counter.wrapping_sub(offset) % self.universe
+                if offset < self.fresh {
+                    // The newest keys are the ones the op mix inserted
+                    self.universe + self.fresh - offset
+                } else {
+                    // Access keys near the most recent insert, wrapping around
+                    self.insert_counter.wrapping_sub(offset) % self.universe
+                }
             }
 
             Workload::ShiftingHotspot {
@@ -424,24 +860,7 @@ impl WorkloadGenerator {
                 }
             }
 
-            Workload::Mixture => {
-                // Default mixture: 70% Zipfian, 20% Scan-like, 10% Uniform
-                let r = self.rng.random::<f64>();
-                if r < 0.7 {
-                    // Zipfian-like with manual calculation
-                    let rank =
-                        (1.0 / self.rng.random::<f64>().max(0.001)).min(self.universe as f64);
-                    (rank as u64).saturating_sub(1).min(self.universe - 1)
-                } else if r < 0.9 {
-                    // Sequential scan behavior
-                    let key = self.scan_pos;
-                    self.scan_pos = (self.scan_pos + 1) % self.universe;
-                    key
-                } else {
-                    // Uniform random
-                    self.rng.random::<u64>() % self.universe
-                }
-            }
+            Workload::Mixture { .. } => self.mixture.as_mut().unwrap().next_key(),
         }
     }
 }
@@ -481,22 +900,29 @@ impl HitRate {
 // EventSource Integration
 // ============================================================================
 
-use crate::event::Event;
+use crate::event::{Event, Op};
 use crate::source::EventSource;
 
 /// `WorkloadGenerator` implements `EventSource` as an infinite stream.
 ///
-/// Each call to `next_event` returns a Get event with the next generated key.
-/// Use `BoundedGenerator` to limit the number of events.
+/// Each call to `next_event` returns an event for the next generated key:
+/// always a Get with the default read-only [`OpMix`], otherwise an operation
+/// drawn from the mix. Use `BoundedGenerator` to limit the number of events.
 impl EventSource for WorkloadGenerator {
     fn next_event(&mut self) -> Option<Event> {
-        Some(Event::get(self.next_key()))
+        let event = match self.pending.take() {
+            Some(event) => event,
+            None => self.next_op(),
+        };
+        Some(self.stamped(event))
     }
 }
 
 /// Bounded wrapper that limits the number of events from a generator.
 ///
-/// Use this to create finite traces from infinite generators.
+/// Use this to create finite traces from infinite generators. The bound
+/// counts events, so a read-modify-write counts twice and may be cut in half
+/// at the end.
 #[derive(Debug, Clone)]
 pub struct BoundedGenerator {
     inner: WorkloadGenerator,
@@ -541,7 +967,7 @@ impl EventSource for BoundedGenerator {
             return None;
         }
         self.remaining -= 1;
-        Some(Event::get(self.inner.next_key()))
+        self.inner.next_event()
     }
 
     fn size_hint(&self) -> Option<usize> {